@@ -0,0 +1,203 @@
+use syn::{meta::ParseNestedMeta, Attribute, LitStr, Result};
+
+/// Stores an option value, rejecting the option if it is given twice.
+fn set<T>(slot: &mut Option<T>, value: T, meta: &ParseNestedMeta) -> Result<()> {
+    if slot.is_some() {
+        return Err(meta.error("duplicate derse attribute"));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+/// Sets a flag option, rejecting the option if it is given twice.
+fn set_flag(flag: &mut bool, meta: &ParseNestedMeta) -> Result<()> {
+    if *flag {
+        return Err(meta.error("duplicate derse attribute"));
+    }
+    *flag = true;
+    Ok(())
+}
+
+/// Calls `f` for each option inside the `#[derse(...)]` attributes.
+fn parse_derse_attrs(
+    attrs: &[Attribute],
+    mut f: impl FnMut(ParseNestedMeta) -> Result<()>,
+) -> Result<()> {
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("derse")) {
+        attr.parse_nested_meta(&mut f)?;
+    }
+    Ok(())
+}
+
+/// Options parsed from the `#[derse(...)]` attributes of a field.
+#[derive(Default)]
+pub(crate) struct FieldAttrs {
+    /// The field is neither serialized nor deserialized.
+    pub skip: bool,
+    /// A function that provides the value of a missing field.
+    pub default: Option<syn::Path>,
+    /// A module providing `serialize_to` and `deserialize_from` for the field.
+    pub with: Option<syn::Path>,
+    /// The integer field is written as a `VarInt64`, zig-zag encoded if signed.
+    pub varint: bool,
+    /// The explicit id of the field in a tagged struct.
+    pub id: Option<syn::LitInt>,
+}
+
+impl FieldAttrs {
+    /// Parses the field options from a list of attributes.
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut result = Self::default();
+        parse_derse_attrs(attrs, |meta| {
+            if meta.path.is_ident("skip") {
+                set_flag(&mut result.skip, &meta)?;
+            } else if meta.path.is_ident("default") {
+                let path = meta.value()?.parse::<LitStr>()?.parse()?;
+                set(&mut result.default, path, &meta)?;
+            } else if meta.path.is_ident("with") {
+                let path = meta.value()?.parse::<LitStr>()?.parse()?;
+                set(&mut result.with, path, &meta)?;
+            } else if meta.path.is_ident("varint") {
+                set_flag(&mut result.varint, &meta)?;
+            } else if meta.path.is_ident("id") {
+                let id = meta.value()?.parse()?;
+                set(&mut result.id, id, &meta)?;
+            } else {
+                return Err(meta.error("unknown derse field attribute"));
+            }
+            if result.skip && (result.with.is_some() || result.id.is_some()) {
+                return Err(meta.error("`skip` cannot be combined with `with` or `id`"));
+            }
+            if result.varint && (result.skip || result.with.is_some()) {
+                return Err(meta.error("`varint` cannot be combined with `skip` or `with`"));
+            }
+            Ok(())
+        })?;
+        Ok(result)
+    }
+}
+
+/// How the variants of an enum are identified in the serialized data.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum TagKind {
+    /// Variants are identified by their names.
+    Name,
+    /// Variants are identified by numeric ids encoded as `VarInt64`.
+    Index,
+}
+
+/// Options parsed from the `#[derse(...)]` attributes of a struct or enum.
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    /// The variant tag kind set by `tag = "name"` or `tag = "index"`.
+    pub tag: Option<(TagKind, LitStr)>,
+    /// Struct fields are written as id/length/value entries.
+    pub tagged: Option<syn::Path>,
+    /// A single-field struct is written exactly like its field.
+    pub transparent: Option<syn::Path>,
+    /// The path to the `derse` crate used by the generated code.
+    pub krate: Option<syn::Path>,
+    /// The type the value is converted into before serializing.
+    pub into: Option<syn::Type>,
+    /// The type deserialized and converted into the value with `From`.
+    pub from: Option<syn::Type>,
+    /// The type deserialized and converted into the value with `TryFrom`.
+    pub try_from: Option<syn::Type>,
+    /// A function checking the value after it is deserialized.
+    pub validate: Option<syn::Path>,
+}
+
+impl ContainerAttrs {
+    /// Parses the container options from a list of attributes.
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut result = Self::default();
+        parse_derse_attrs(attrs, |meta| {
+            if meta.path.is_ident("tag") {
+                let value = meta.value()?.parse::<LitStr>()?;
+                let kind = match value.value().as_str() {
+                    "name" => TagKind::Name,
+                    "index" => TagKind::Index,
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            value,
+                            "expected `tag = \"name\"` or `tag = \"index\"`",
+                        ))
+                    }
+                };
+                set(&mut result.tag, (kind, value), &meta)?;
+            } else if meta.path.is_ident("tagged") {
+                set(&mut result.tagged, meta.path.clone(), &meta)?;
+            } else if meta.path.is_ident("transparent") {
+                set(&mut result.transparent, meta.path.clone(), &meta)?;
+            } else if meta.path.is_ident("crate") {
+                let path = meta.value()?.parse::<LitStr>()?.parse()?;
+                set(&mut result.krate, path, &meta)?;
+            } else if meta.path.is_ident("into") {
+                let ty = meta.value()?.parse::<LitStr>()?.parse()?;
+                set(&mut result.into, ty, &meta)?;
+            } else if meta.path.is_ident("from") {
+                let ty = meta.value()?.parse::<LitStr>()?.parse()?;
+                set(&mut result.from, ty, &meta)?;
+            } else if meta.path.is_ident("try_from") {
+                let ty = meta.value()?.parse::<LitStr>()?.parse()?;
+                set(&mut result.try_from, ty, &meta)?;
+            } else if meta.path.is_ident("validate") {
+                let path = meta.value()?.parse::<LitStr>()?.parse()?;
+                set(&mut result.validate, path, &meta)?;
+            } else {
+                return Err(meta.error("unknown derse container attribute"));
+            }
+            if result.from.is_some() && result.try_from.is_some() {
+                return Err(meta.error("`from` and `try_from` cannot be combined"));
+            }
+            let convert =
+                result.into.is_some() || result.from.is_some() || result.try_from.is_some();
+            let layout =
+                result.transparent.is_some() || result.tagged.is_some() || result.tag.is_some();
+            if convert && layout {
+                return Err(meta.error(
+                    "`into`, `from` and `try_from` cannot be combined with `transparent`, `tagged` or `tag`",
+                ));
+            }
+            Ok(())
+        })?;
+        Ok(result)
+    }
+}
+
+/// Options parsed from the `#[derse(...)]` attributes of an enum variant.
+#[derive(Default)]
+pub(crate) struct VariantAttrs {
+    /// The explicit numeric id of the variant.
+    pub id: Option<syn::LitInt>,
+    /// The name written in place of the variant identifier.
+    pub rename: Option<LitStr>,
+    /// Additional names accepted when deserializing.
+    pub aliases: Vec<LitStr>,
+    /// The variant is used for tags that match no other variant.
+    pub other: bool,
+}
+
+impl VariantAttrs {
+    /// Parses the variant options from a list of attributes.
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut result = Self::default();
+        parse_derse_attrs(attrs, |meta| {
+            if meta.path.is_ident("id") {
+                let id = meta.value()?.parse()?;
+                set(&mut result.id, id, &meta)?;
+            } else if meta.path.is_ident("rename") {
+                let name = meta.value()?.parse()?;
+                set(&mut result.rename, name, &meta)?;
+            } else if meta.path.is_ident("alias") {
+                result.aliases.push(meta.value()?.parse()?);
+            } else if meta.path.is_ident("other") {
+                set_flag(&mut result.other, &meta)?;
+            } else {
+                return Err(meta.error("unknown derse variant attribute"));
+            }
+            Ok(())
+        })?;
+        Ok(result)
+    }
+}
@@ -0,0 +1,373 @@
+use crate::attr::{ContainerAttrs, FieldAttrs};
+use crate::{
+    check_no_id, check_struct_only, crate_path, field_ids, other_variant, transparent_field,
+    variant_tags, VariantTag,
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{
+    Data, DataEnum, DataStruct, DataUnion, DeriveInput, Field, Fields, Ident, Lifetime,
+    LifetimeParam,
+};
+
+/// Expands the `Deserialize` derive for the given type.
+pub(crate) fn expand(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let container = ContainerAttrs::parse(&ast.attrs)?;
+    let krate = crate_path(&container);
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let mut generics = ast.generics.clone();
+    let (impl_generics, lifetime) = if let Some(lifetime) = ast.generics.lifetimes().next().cloned()
+    {
+        (impl_generics, quote! { #lifetime })
+    } else {
+        let lifetime = Lifetime::new("'derse", proc_macro2::Span::call_site());
+        let lifetime_param = LifetimeParam::new(lifetime.clone());
+        let generic_param = syn::GenericParam::Lifetime(lifetime_param);
+        generics.params.insert(0, generic_param);
+        let (impl_generics, _, _) = generics.split_for_impl();
+        (impl_generics, quote! { #lifetime })
+    };
+
+    let struct_type = &ast.ident;
+    let validate = container.validate.as_ref().map(|validate| {
+        quote! { #validate(&result).map_err(#krate::Error::InvalidValue)?; }
+    });
+    let convert = match (&container.from, &container.try_from) {
+        (Some(from), _) => Some(quote! {
+            let proxy: #from = #krate::Deserialize::deserialize_from(buf)?;
+            let result: Self = ::core::convert::From::from(proxy);
+        }),
+        (_, Some(try_from)) => Some(quote! {
+            let proxy: #try_from = #krate::Deserialize::deserialize_from(buf)?;
+            let result: Self = ::core::convert::TryFrom::try_from(proxy)
+                .map_err(|e| #krate::Error::InvalidValue(::std::string::ToString::to_string(&e)))?;
+        }),
+        (None, None) => None,
+    };
+    // Transparent and converted types have no length prefix of their own, but still
+    // count as a nesting level, since they may be recursive through `Option` or `Box`.
+    let nested = |body: TokenStream| {
+        quote! {
+            impl #impl_generics #struct_type #ty_generics #where_clause {
+                fn __derse_deserialize_value<Deserializer: #krate::Deserializer<#lifetime>>(buf: &mut Deserializer) -> #krate::Result<Self> {
+                    #body
+                }
+            }
+
+            impl #impl_generics #krate::Deserialize<#lifetime> for #struct_type #ty_generics #where_clause {
+                fn deserialize_from<Deserializer: #krate::Deserializer<#lifetime>>(buf: &mut Deserializer) -> #krate::Result<Self>
+                where
+                    Self: Sized,
+                {
+                    buf.enter_nested()?;
+                    let result = Self::__derse_deserialize_value(buf);
+                    buf.leave_nested();
+                    result
+                }
+            }
+        }
+    };
+    if let Some(convert) = convert {
+        return Ok(nested(quote! {
+            #convert
+            #validate
+            Ok(result)
+        }));
+    }
+
+    let struct_name = struct_type.to_string();
+    let deserialize_statements = match &ast.data {
+        Data::Struct(DataStruct { fields, .. }) => {
+            if let Some((_, tag)) = &container.tag {
+                return Err(syn::Error::new_spanned(
+                    tag,
+                    "`tag` is only supported on enums",
+                ));
+            }
+            if let Some((field, attrs)) = transparent_field(&container, fields)? {
+                let value = deserialize_value(&krate, &attrs, quote! { buf }, None);
+                let value = match &field.ident {
+                    Some(ident) => quote! { { #ident: #value } },
+                    None => quote! { ( #value ) },
+                };
+                return Ok(nested(quote! {
+                    let result = Self #value;
+                    #validate
+                    Ok(result)
+                }));
+            }
+            if container.tagged.is_some() {
+                deserialize_tagged_fields(&krate, fields)?
+            } else {
+                let constructor = deserialize_fields(&krate, fields, None)?;
+                quote! { let result = Self #constructor; }
+            }
+        }
+        Data::Enum(DataEnum { variants, .. }) => {
+            check_struct_only(&container)?;
+            let tags = variant_tags(&container, variants)?;
+            let numeric = matches!(tags.first(), Some(VariantTag::Index(_)));
+            let other = other_variant(variants)?;
+            let mut match_statements = Vec::new();
+            for (variant, tag) in variants.iter().zip(tags) {
+                let ident = &variant.ident;
+                if other.is_some_and(|other| other.ident == *ident && !other.fields.is_empty()) {
+                    continue;
+                }
+                let pattern = match tag {
+                    VariantTag::Name(name, aliases) => quote! { #name #(| #aliases)* },
+                    VariantTag::Index(id) => quote! { #id },
+                };
+                let constructor = deserialize_fields(&krate, &variant.fields, Some(ident))?;
+                match_statements.push(quote! { #pattern => Self::#ident #constructor, });
+            }
+            let (ty, scrutinee) = if numeric {
+                (
+                    quote! { let ty = #krate::VarInt64::deserialize_from(buf)?.0; },
+                    quote! { ty },
+                )
+            } else {
+                (
+                    quote! { let ty = <::std::borrow::Cow<str>>::deserialize_from(buf)?; },
+                    quote! { &*ty },
+                )
+            };
+            let fallback = match other {
+                Some(other) => {
+                    let ident = &other.ident;
+                    let values = [
+                        quote! { ::core::convert::From::from(ty) },
+                        quote! {
+                            {
+                                let mut body = buf.pop(buf.remaining())?.into_owned();
+                                // `remaining` may undercount for deserializers not reporting it.
+                                while !buf.is_empty() {
+                                    body.extend_from_slice(&buf.pop(1)?);
+                                }
+                                ::core::convert::From::from(body)
+                            }
+                        },
+                    ];
+                    let values = other
+                        .fields
+                        .iter()
+                        .zip(values)
+                        .map(|(f, value)| match &f.ident {
+                            Some(ident) => quote! { #ident: #value },
+                            None => value,
+                        });
+                    match &other.fields {
+                        Fields::Named(_) => quote! { _ => Self::#ident { #(#values, )* }, },
+                        Fields::Unnamed(_) => quote! { _ => Self::#ident ( #(#values, )* ), },
+                        Fields::Unit => quote! { _ => Self::#ident, },
+                    }
+                }
+                None => quote! {
+                    _ => return Err(#krate::Error::InvalidType(format!("{}::{}", #struct_name, ty))),
+                },
+            };
+            quote! {
+                #ty
+                let result = match #scrutinee {
+                    #(#match_statements)*
+                    #fallback
+                };
+            }
+        }
+        Data::Union(DataUnion { union_token, .. }) => {
+            return Err(syn::Error::new_spanned(
+                union_token,
+                "only struct and enum are supported",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics #struct_type #ty_generics #where_clause {
+            fn __derse_deserialize_fields<Deserializer: #krate::Deserializer<#lifetime>>(buf: &mut Deserializer) -> #krate::Result<Self> {
+                use #krate::Deserialize;
+                #deserialize_statements
+                #validate
+                Ok(result)
+            }
+        }
+
+        impl #impl_generics #krate::DetailedDeserialize<#lifetime> for #struct_type #ty_generics #where_clause {
+            fn deserialize_len<Deserializer: #krate::Deserializer<#lifetime>>(buf: &mut Deserializer) -> #krate::Result<usize> {
+                use #krate::Deserialize;
+                Ok(#krate::VarInt64::deserialize_from(buf)?.0 as usize)
+            }
+
+            fn deserialize_fields<Deserializer: #krate::Deserializer<#lifetime>>(buf: &mut Deserializer) -> #krate::Result<Self>
+            where
+                Self: Sized,
+            {
+                Self::__derse_deserialize_fields(buf).map_err(|e| e.context(buf.remaining()))
+            }
+        }
+
+        impl #impl_generics #krate::Deserialize<#lifetime> for #struct_type #ty_generics #where_clause {
+            fn deserialize_from<Deserializer: #krate::Deserializer<#lifetime>>(buf: &mut Deserializer) -> #krate::Result<Self>
+            where
+                Self: Sized,
+            {
+                use #krate::DetailedDeserialize;
+                let start = buf.remaining();
+                buf.enter_nested()?;
+                let result = Self::deserialize_len(buf)
+                    .and_then(|len| buf.advance(len))
+                    .and_then(|mut buf| Self::deserialize_fields(&mut buf));
+                buf.leave_nested();
+                result.map_err(|e| e.with_type(#struct_name, start, buf.remaining()))
+            }
+        }
+    })
+}
+
+/// Returns the expression producing the value of a missing or skipped field.
+fn default_value(attrs: &FieldAttrs) -> TokenStream {
+    match &attrs.default {
+        Some(default) => quote! { #default() },
+        None => quote! { Default::default() },
+    }
+}
+
+/// Returns the expression deserializing a field from the deserializer `buf`.
+///
+/// Errors are passed through `context`, an expression of the error `e`, if given.
+fn deserialize_value(
+    krate: &TokenStream,
+    attrs: &FieldAttrs,
+    buf: TokenStream,
+    context: Option<TokenStream>,
+) -> TokenStream {
+    let value = match &attrs.with {
+        Some(with) => quote! { #with::deserialize_from(#buf) },
+        None if attrs.varint => quote! { #krate::varint::deserialize_from(#buf) },
+        None => quote! { #krate::Deserialize::deserialize_from(#buf) },
+    };
+    match context {
+        Some(context) => quote! { #value.map_err(|e: #krate::Error| #context)? },
+        None => quote! { #value? },
+    }
+}
+
+/// Returns the name of a field in error paths, prefixed by its variant if any.
+fn field_name(f: &Field, index: usize, variant: Option<&Ident>) -> String {
+    let name = match &f.ident {
+        Some(ident) => ident.unraw().to_string(),
+        None => index.to_string(),
+    };
+    match variant {
+        Some(variant) => format!("{}.{}", variant, name),
+        None => name,
+    }
+}
+
+/// Generates the constructor body deserializing the given fields in order.
+fn deserialize_fields(
+    krate: &TokenStream,
+    fields: &Fields,
+    variant: Option<&Ident>,
+) -> syn::Result<TokenStream> {
+    let mut statements = Vec::new();
+    for (i, f) in fields.iter().enumerate() {
+        let attrs = FieldAttrs::parse(&f.attrs)?;
+        check_no_id(&attrs)?;
+        let default = default_value(&attrs);
+        let statement = if attrs.skip {
+            default
+        } else {
+            let name = field_name(f, i, variant);
+            let context = quote! { e.with_field(#name, buf.remaining()) };
+            let value = deserialize_value(krate, &attrs, quote! { buf }, Some(context));
+            quote! {
+                if buf.is_empty() {
+                    #default
+                } else {
+                    #value
+                }
+            }
+        };
+        statements.push(
+            f.ident
+                .as_ref()
+                .map_or(statement.clone(), |ident| quote! {#ident: #statement}),
+        );
+    }
+    Ok(match fields {
+        Fields::Named(_) => quote! { { #(#statements, )* } },
+        Fields::Unnamed(_) => quote! { ( #(#statements, )* ) },
+        Fields::Unit => quote! {},
+    })
+}
+
+/// Generates the statements deserializing a tagged struct into `result`.
+///
+/// Entries with unknown ids are skipped, and absent fields take their default value.
+fn deserialize_tagged_fields(krate: &TokenStream, fields: &Fields) -> syn::Result<TokenStream> {
+    let mut vars = Vec::new();
+    let mut arms = Vec::new();
+    let mut statements = Vec::new();
+    for (i, ((attrs, id), f)) in field_ids(fields)?.into_iter().zip(fields).enumerate() {
+        let default = default_value(&attrs);
+        let statement = match id {
+            Some(id) => {
+                let var = syn::Ident::new(&format!("field_{i}"), proc_macro2::Span::call_site());
+                let name = field_name(f, i, None);
+                let context = quote! {
+                    e.with_field(#name, field.remaining()).advanced(buf.remaining())
+                };
+                let value = deserialize_value(krate, &attrs, quote! { &mut field }, Some(context));
+                arms.push(quote! { #id => #var = Some(#value), });
+                let statement = quote! {
+                    match #var {
+                        Some(value) => value,
+                        None => #default,
+                    }
+                };
+                vars.push(var);
+                statement
+            }
+            None => default,
+        };
+        statements.push(
+            f.ident
+                .as_ref()
+                .map_or(statement.clone(), |ident| quote! {#ident: #statement}),
+        );
+    }
+    let constructor = match fields {
+        Fields::Named(_) => quote! { { #(#statements, )* } },
+        Fields::Unnamed(_) => quote! { ( #(#statements, )* ) },
+        Fields::Unit => quote! {},
+    };
+
+    let entry = if arms.is_empty() {
+        quote! {
+            #krate::VarInt64::deserialize_from(buf)?;
+            let len = #krate::VarInt64::deserialize_from(buf)?.0 as usize;
+            buf.advance(len)?;
+        }
+    } else {
+        quote! {
+            let id = #krate::VarInt64::deserialize_from(buf)?.0;
+            let len = #krate::VarInt64::deserialize_from(buf)?.0 as usize;
+            let mut field = buf.advance(len)?;
+            match id {
+                #(#arms)*
+                _ => {}
+            }
+        }
+    };
+
+    Ok(quote! {
+        #( let mut #vars = None; )*
+        while !buf.is_empty() {
+            #entry
+        }
+        let result = Self #constructor;
+    })
+}
@@ -1,222 +1,350 @@
 //! This crate provides procedural macros for automatically deriving serialization and deserialization implementations for custom types.
 //! It leverages the `derse` crate for serialization and deserialization functionalities.
 
+mod attr;
+mod de;
+mod ser;
+
+use attr::{ContainerAttrs, FieldAttrs, TagKind, VariantAttrs};
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
-    parse_macro_input, Data, DataEnum, DataStruct, DeriveInput, Fields, Lifetime, LifetimeParam,
+    parse_macro_input, punctuated::Punctuated, token::Comma, DeriveInput, Field, Fields, LitStr,
+    Variant,
 };
 
 /// Derives the `Serialize` trait for structs and enums.
 ///
 /// This macro generates an implementation of the `Serialize` trait for the given type.
 /// It supports both structs and enums, handling named, unnamed, and unit fields.
-#[proc_macro_derive(Serialize)]
+///
+/// Fields accept the following `#[derse(...)]` attributes:
+///
+/// * `skip` - the field is not serialized.
+/// * `with = "module"` - the field is serialized by `module::serialize_to`.
+/// * `varint` - the integer field is written as a `VarInt64`, zig-zag encoded if signed.
+///
+/// Structs marked with `#[derse(tagged)]` write each field as a `VarInt64` id, a `VarInt64`
+/// length and the value, so fields can be removed or reordered without breaking old data.
+/// Field ids are set with `#[derse(id = N)]`, and otherwise follow the previous field's id.
+///
+/// Single-field structs marked with `#[derse(transparent)]` are written exactly like their
+/// field, without the length prefix, so a newtype can replace its inner type on the wire.
+///
+/// Enums are tagged by variant name unless `#[derse(tag = "index")]` is set on the enum
+/// or `#[derse(id = N)]` on a variant, in which case the variant id is written as a `VarInt64`.
+/// With name tags, `#[derse(rename = "name")]` changes the name written for a variant.
+///
+/// With `#[derse(into = "Type")]`, the value is cloned, converted with `Into` and serialized
+/// as `Type` instead, which requires the type to implement `Clone`.
+///
+/// `serialize_borrowed_to` is generated as well, passing the fields to their own
+/// `serialize_borrowed_to`, except for `with` and `varint` fields and `into` types, which
+/// are serialized through `serialize_to`.
+///
+/// The generated code refers to the `derse` crate found in `Cargo.toml`. When it is
+/// re-exported from another crate, set the path with `#[derse(crate = "path::to::derse")]`.
+#[proc_macro_derive(Serialize, attributes(derse))]
 pub fn derse_serialize_derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
-    let krate = get_crate_name();
-    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
-
-    let struct_type = &ast.ident;
-    let statements = match ast.data {
-        Data::Struct(DataStruct { fields, .. }) => {
-            let mut idents = fields
-                .iter()
-                .enumerate()
-                .map(|(i, f)| {
-                    let index = syn::Index::from(i);
-                    f.ident
-                        .as_ref()
-                        .map_or(quote! {#index}, |ident| quote! {#ident})
-                })
-                .collect::<Vec<_>>();
-            idents.reverse();
-            quote! { #( self.#idents.serialize_to(serializer)?; )* }
-        }
-        Data::Enum(DataEnum { variants, .. }) => {
-            let mut match_statements = Vec::new();
-            for variant in variants {
-                let ident = &variant.ident;
-                let name = ident.to_string();
-                let match_statement = match variant.fields {
-                    Fields::Named(fields) => {
-                        let mut idents = fields.named.iter().map(|f| &f.ident).collect::<Vec<_>>();
-                        let list = quote! { #(#idents, )* };
-                        idents.reverse();
-                        quote! {
-                            Self::#ident { #list } => {
-                                #( #idents.serialize_to(serializer)?; )*
-                                #name.serialize_to(serializer)?;
-                            }
-                        }
-                    }
-                    Fields::Unnamed(fields) => {
-                        let mut idents = fields
-                            .unnamed
-                            .iter()
-                            .enumerate()
-                            .map(|(i, _)| {
-                                syn::Ident::new(&format!("v{i}"), proc_macro2::Span::call_site())
-                            })
-                            .collect::<Vec<_>>();
-                        let list = quote! { #(#idents, )* };
-                        idents.reverse();
-                        quote! {
-                            Self::#ident ( #list ) => {
-                                #( #idents.serialize_to(serializer)?; )*
-                                #name.serialize_to(serializer)?;
-                            }
-                        }
-                    }
-                    Fields::Unit => quote! {
-                        Self::#ident => {
-                            #name.serialize_to(serializer)?;
-                        }
-                    },
-                };
-                match_statements.push(match_statement);
-            }
-            quote! {
-                match self {
-                    #(#match_statements)*
-                }
-            }
-        }
-        _ => panic!("only struct and enum are supported"),
-    };
-
-    quote! {
-        impl #impl_generics #krate::Serialize for #struct_type #ty_generics #where_clause {
-            fn serialize_to<Serializer: #krate::Serializer>(&self, serializer: &mut Serializer) -> #krate::Result<()> {
-                let start = serializer.len();
-                #statements
-                let len = serializer.len() - start;
-                #krate::VarInt64(len as u64).serialize_to(serializer)
-            }
-        }
-    }.into()
+    ser::expand(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
 /// Derives the `Deserialize` trait for structs and enums.
 ///
 /// This macro generates an implementation of the `Deserialize` trait for the given type.
 /// It supports both structs and enums, handling named, unnamed, and unit fields.
-#[proc_macro_derive(Deserialize)]
+///
+/// Fields accept the following `#[derse(...)]` attributes:
+///
+/// * `skip` - the field is not deserialized and always takes its default value.
+/// * `default = "path"` - missing fields are filled by calling `path()` instead of `Default::default()`.
+/// * `with = "module"` - the field is deserialized by `module::deserialize_from`.
+/// * `varint` - the integer field is read as written by `varint` in the `Serialize` derive.
+///
+/// Tagged structs skip entries with unknown ids, and fields whose id is absent take their
+/// default value.
+///
+/// Transparent structs only implement `Deserialize`, since they have no length prefix to
+/// split off with `DetailedDeserialize`.
+///
+/// Enums accept the same `tag`, `id` and `rename` attributes as the `Serialize` derive.
+/// With name tags, `#[derse(alias = "name")]` lets a variant also accept an older name,
+/// and may be repeated.
+///
+/// A variant marked with `#[derse(other)]` is used for unknown tags instead of returning
+/// `Error::InvalidType`. It is either a unit variant, or has a tag field (`String` for
+/// name tags, `u64` for numeric tags) optionally followed by a `Vec<u8>` field receiving
+/// the rest of the enum's data, which is written back unchanged when serialized.
+///
+/// With `#[derse(from = "Type")]`, a `Type` is deserialized and converted with `From`.
+/// `#[derse(try_from = "Type")]` uses `TryFrom` instead, and conversion errors are returned
+/// as `Error::InvalidValue` with the error message. Like transparent structs, these types
+/// only implement `Deserialize`, so they cannot be decoded by `DerseCodec`, which requires
+/// `DetailedDeserialize`. The conversion attributes cannot be combined with `transparent`,
+/// `tagged` or `tag`, which describe a layout the proxy type replaces.
+///
+/// `#[derse(validate = "path")]` calls `path(&value)` after the value is deserialized, where
+/// `path` is a `fn(&Self) -> Result<(), String>`, and turns an error into `Error::InvalidValue`.
+///
+/// Errors are wrapped in `Error::Context`, recording the type name, the path of the failing
+/// field such as `Outer.items[3].name`, and the offset of the error. This changes the errors
+/// returned by derived types: code matching a variant such as `Error::DataIsShort` must match
+/// on `error.root_cause()` instead.
+///
+/// Like the `Serialize` derive, `#[derse(crate = "path::to::derse")]` overrides the crate path.
+#[proc_macro_derive(Deserialize, attributes(derse))]
 pub fn derse_deserialize_derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
-    let krate = get_crate_name();
+    de::expand(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
-    let mut generics = ast.generics.clone();
-    let (impl_generics, lifetime) = if let Some(lifetime) = ast.generics.lifetimes().next().cloned()
-    {
-        (impl_generics, quote! { #lifetime })
-    } else {
-        let lifetime = Lifetime::new("'derse", proc_macro2::Span::call_site());
-        let lifetime_param = LifetimeParam::new(lifetime.clone());
-        let generic_param = syn::GenericParam::Lifetime(lifetime_param);
-        generics.params.insert(0, generic_param);
-        let (impl_generics, _, _) = generics.split_for_impl();
-        (impl_generics, quote! { #lifetime })
-    };
+/// The value identifying an enum variant in the serialized data.
+enum VariantTag {
+    /// The serialized name and the aliases accepted when deserializing.
+    Name(String, Vec<String>),
+    /// The numeric id.
+    Index(u64),
+}
 
-    let struct_type = &ast.ident;
-    let struct_name = struct_type.to_string();
-    let deserialize_statements = match ast.data {
-        Data::Struct(DataStruct { fields, .. }) => {
-            let statements = fields
-                .iter()
-                .map(|f| {
-                    let statement = quote! {
-                        if buf.is_empty() {
-                            Default::default()
-                        } else {
-                            derse::Deserialize::deserialize_from(buf)?
-                        }
-                    };
-                    f.ident
-                        .as_ref()
-                        .map_or(statement.clone(), |ident| quote! {#ident: #statement})
-                })
-                .collect::<Vec<_>>();
-            match fields {
-                Fields::Named(_) => quote! { let result = Self { #(#statements, )* }; },
-                Fields::Unnamed(_) => quote! { let result = Self ( #(#statements, )* ); },
-                Fields::Unit => quote! { let result = Self; },
+/// Returns how the variants of an enum are identified, defaulting to names unless
+/// a variant has an explicit `id`.
+fn tag_kind(container: &ContainerAttrs, variants: &Punctuated<Variant, Comma>) -> TagKind {
+    container
+        .tag
+        .as_ref()
+        .map(|(kind, _)| *kind)
+        .unwrap_or_else(|| {
+            let has_id = variants.iter().any(|variant| {
+                VariantAttrs::parse(&variant.attrs).is_ok_and(|attrs| attrs.id.is_some())
+            });
+            if has_id {
+                TagKind::Index
+            } else {
+                TagKind::Name
             }
+        })
+}
+
+/// Finds the variant marked with `#[derse(other)]`.
+///
+/// The variant is either a unit variant, or has up to two fields which receive the
+/// unknown tag and the remaining bytes of the enum.
+fn other_variant(variants: &Punctuated<Variant, Comma>) -> syn::Result<Option<&Variant>> {
+    let mut other = None;
+    for variant in variants {
+        if !VariantAttrs::parse(&variant.attrs)?.other {
+            continue;
         }
-        Data::Enum(DataEnum { variants, .. }) => {
-            let mut match_statements = Vec::new();
-            for variant in variants {
-                let ident = &variant.ident;
-                let variant_name = ident.to_string();
-                let statements = variant
-                    .fields
-                    .iter()
-                    .map(|f| {
-                        let statement = quote! {
-                            if buf.is_empty() {
-                                Default::default()
-                            } else {
-                                derse::Deserialize::deserialize_from(buf)?
-                            }
-                        };
-                        f.ident
-                            .as_ref()
-                            .map_or(statement.clone(), |ident| quote! {#ident: #statement})
-                    })
-                    .collect::<Vec<_>>();
-                let match_statement = match variant.fields {
-                    Fields::Named(_) => {
-                        quote! { #variant_name => Self::#ident { #(#statements, )* }, }
-                    }
-                    Fields::Unnamed(_) => {
-                        quote! { #variant_name => Self::#ident ( #(#statements, )* ), }
-                    }
-                    Fields::Unit => quote! { #variant_name => Self::#ident, },
-                };
-                match_statements.push(match_statement);
-            }
-            quote! {
-                let ty = <&str>::deserialize_from(buf)?;
-                let result = match ty {
-                    #(#match_statements)*
-                    _ => return Err(derse::Error::InvalidType(format!("{}::{}", #struct_name, ty))),
-                };
-            }
+        if other.is_some() {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                "only one variant can be marked with `other`",
+            ));
         }
-        _ => panic!("only struct and enum are supported"),
-    };
+        if variant.fields.len() > 2 {
+            return Err(syn::Error::new_spanned(
+                &variant.fields,
+                "the `other` variant takes at most a tag and a body field",
+            ));
+        }
+        other = Some(variant);
+    }
+    Ok(other)
+}
 
-    quote! {
-        impl #impl_generics #krate::DetailedDeserialize<#lifetime> for #struct_type #ty_generics #where_clause {
-            fn deserialize_len<Deserializer: #krate::Deserializer<#lifetime>>(buf: &mut Deserializer) -> #krate::Result<usize> {
-                use #krate::Deserialize;
-                Ok(#krate::VarInt64::deserialize_from(buf)?.0 as usize)
-            }
+/// Computes the tags of the enum variants from the container and variant attributes.
+///
+/// Variants without an explicit `id` take the id of the previous variant plus one,
+/// starting from zero, just like the discriminants of a Rust enum.
+fn variant_tags(
+    container: &ContainerAttrs,
+    variants: &Punctuated<Variant, Comma>,
+) -> syn::Result<Vec<VariantTag>> {
+    let attrs = variants
+        .iter()
+        .map(|variant| VariantAttrs::parse(&variant.attrs))
+        .collect::<syn::Result<Vec<_>>>()?;
 
-            fn deserialize_fields<Deserializer: #krate::Deserializer<#lifetime>>(buf: &mut Deserializer) -> #krate::Result<Self>
-            where
-                Self: Sized,
-            {
-                use #krate::Deserialize;
-                #deserialize_statements
-                Ok(result)
+    let kind = tag_kind(container, variants);
+    let mut tags = Vec::new();
+    if kind == TagKind::Name {
+        if let Some(id) = attrs.iter().find_map(|a| a.id.as_ref()) {
+            return Err(syn::Error::new_spanned(
+                id,
+                "`id` is not allowed with `tag = \"name\"`",
+            ));
+        }
+        let mut seen = std::collections::HashSet::new();
+        for (variant, attrs) in variants.iter().zip(attrs) {
+            let name = attrs
+                .rename
+                .as_ref()
+                .map_or_else(|| variant.ident.to_string(), LitStr::value);
+            let aliases = attrs.aliases.iter().map(LitStr::value).collect::<Vec<_>>();
+            for name in std::iter::once(&name).chain(&aliases) {
+                if !seen.insert(name.clone()) {
+                    return Err(syn::Error::new_spanned(
+                        &variant.ident,
+                        format!("duplicate variant name {name:?}"),
+                    ));
+                }
             }
+            tags.push(VariantTag::Name(name, aliases));
         }
+        return Ok(tags);
+    }
 
-        impl #impl_generics #krate::Deserialize<#lifetime> for #struct_type #ty_generics #where_clause {
-            fn deserialize_from<Deserializer: #krate::Deserializer<#lifetime>>(buf: &mut Deserializer) -> #krate::Result<Self>
-            where
-                Self: Sized,
-            {
-                use #krate::DetailedDeserialize;
-                let len = Self::deserialize_len(buf)?;
-                let mut buf = buf.advance(len)?;
-                Self::deserialize_fields(&mut buf)
-            }
+    if let Some(name) = attrs
+        .iter()
+        .find_map(|a| a.rename.as_ref().or(a.aliases.first()))
+    {
+        return Err(syn::Error::new_spanned(
+            name,
+            "`rename` and `alias` are not allowed with numeric tags",
+        ));
+    }
+    let mut seen = std::collections::HashSet::new();
+    let mut next = 0u64;
+    for (variant, attrs) in variants.iter().zip(attrs) {
+        let id = match &attrs.id {
+            Some(id) => id.base10_parse()?,
+            None => next,
+        };
+        if !seen.insert(id) {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                format!("duplicate variant id {id}"),
+            ));
+        }
+        next = id.wrapping_add(1);
+        tags.push(VariantTag::Index(id));
+    }
+    Ok(tags)
+}
+
+/// Returns the member used to access the `i`-th field.
+fn field_member(i: usize, field: &Field) -> proc_macro2::TokenStream {
+    let index = syn::Index::from(i);
+    field
+        .ident
+        .as_ref()
+        .map_or(quote! {#index}, |ident| quote! {#ident})
+}
+
+/// Returns the identifiers that enum variant fields are bound to.
+fn field_bindings(fields: &Fields) -> Vec<syn::Ident> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            f.ident.clone().unwrap_or_else(|| {
+                syn::Ident::new(&format!("v{i}"), proc_macro2::Span::call_site())
+            })
+        })
+        .collect()
+}
+
+/// Rejects field ids outside of tagged structs.
+fn check_no_id(attrs: &FieldAttrs) -> syn::Result<()> {
+    match &attrs.id {
+        Some(id) => Err(syn::Error::new_spanned(
+            id,
+            "field `id` requires `#[derse(tagged)]` on the struct",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Rejects struct-only container attributes on enums.
+fn check_struct_only(container: &ContainerAttrs) -> syn::Result<()> {
+    if let Some(tagged) = &container.tagged {
+        return Err(syn::Error::new_spanned(
+            tagged,
+            "`tagged` is only supported on structs",
+        ));
+    }
+    if let Some(transparent) = &container.transparent {
+        return Err(syn::Error::new_spanned(
+            transparent,
+            "`transparent` is only supported on structs",
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the only field of a `#[derse(transparent)]` struct with its attributes.
+fn transparent_field<'a>(
+    container: &ContainerAttrs,
+    fields: &'a Fields,
+) -> syn::Result<Option<(&'a Field, FieldAttrs)>> {
+    let Some(transparent) = &container.transparent else {
+        return Ok(None);
+    };
+    if container.tagged.is_some() {
+        return Err(syn::Error::new_spanned(
+            transparent,
+            "`transparent` cannot be combined with `tagged`",
+        ));
+    }
+    let mut iter = fields.iter();
+    let (Some(field), None) = (iter.next(), iter.next()) else {
+        return Err(syn::Error::new_spanned(
+            transparent,
+            "`transparent` requires a struct with exactly one field",
+        ));
+    };
+    let attrs = FieldAttrs::parse(&field.attrs)?;
+    check_no_id(&attrs)?;
+    if attrs.skip {
+        return Err(syn::Error::new_spanned(
+            field,
+            "the field of a `transparent` struct cannot be skipped",
+        ));
+    }
+    Ok(Some((field, attrs)))
+}
+
+/// Computes the ids of the fields of a tagged struct, `None` for skipped fields.
+///
+/// Fields without an explicit `id` take the id of the previous field plus one,
+/// starting from zero.
+fn field_ids(fields: &Fields) -> syn::Result<Vec<(FieldAttrs, Option<u64>)>> {
+    let mut result = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    let mut next = 0u64;
+    for f in fields {
+        let attrs = FieldAttrs::parse(&f.attrs)?;
+        if attrs.skip {
+            result.push((attrs, None));
+            continue;
+        }
+        let id = match &attrs.id {
+            Some(id) => id.base10_parse()?,
+            None => next,
+        };
+        if !seen.insert(id) {
+            return Err(syn::Error::new_spanned(
+                f,
+                format!("duplicate field id {id}"),
+            ));
         }
-    }.into()
+        next = id.wrapping_add(1);
+        result.push((attrs, Some(id)));
+    }
+    Ok(result)
+}
+
+/// Returns the path to the `derse` crate, preferring the `crate` container attribute.
+fn crate_path(container: &ContainerAttrs) -> proc_macro2::TokenStream {
+    match &container.krate {
+        Some(path) => quote! { #path },
+        None => get_crate_name(),
+    }
 }
 
 pub(crate) fn get_crate_name() -> proc_macro2::TokenStream {
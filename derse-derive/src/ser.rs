@@ -0,0 +1,253 @@
+use crate::attr::{ContainerAttrs, FieldAttrs, TagKind};
+use crate::{
+    check_no_id, check_struct_only, crate_path, field_bindings, field_ids, field_member,
+    other_variant, tag_kind, transparent_field, variant_tags, VariantTag,
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DataEnum, DataStruct, DataUnion, DeriveInput, Fields};
+
+/// Expands the `Serialize` derive for the given type.
+pub(crate) fn expand(ast: &DeriveInput) -> syn::Result<TokenStream> {
+    let container = ContainerAttrs::parse(&ast.attrs)?;
+    let krate = crate_path(&container);
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let struct_type = &ast.ident;
+    // Proxies are serialized from a temporary, so they keep the default
+    // `serialize_borrowed_to`.
+    if let Some(into) = &container.into {
+        return Ok(quote! {
+            impl #impl_generics #krate::Serialize for #struct_type #ty_generics #where_clause {
+                fn serialize_to<Serializer: #krate::Serializer>(&self, serializer: &mut Serializer) -> #krate::Result<()> {
+                    let proxy: #into = ::core::convert::Into::into(::core::clone::Clone::clone(self));
+                    #krate::Serialize::serialize_to(&proxy, serializer)
+                }
+            }
+        });
+    }
+
+    if let Data::Struct(DataStruct { fields, .. }) = &ast.data {
+        if let Some((_, tag)) = &container.tag {
+            return Err(syn::Error::new_spanned(
+                tag,
+                "`tag` is only supported on enums",
+            ));
+        }
+        if let Some((field, attrs)) = transparent_field(&container, fields)? {
+            let member = field_member(0, field);
+            let value = quote! { &self.#member };
+            let statement = serialize_value(&krate, &attrs, &value, false);
+            let borrowed = serialize_value(&krate, &attrs, &value, true);
+            return Ok(quote! {
+                impl #impl_generics #krate::Serialize for #struct_type #ty_generics #where_clause {
+                    fn serialize_to<Serializer: #krate::Serializer>(&self, serializer: &mut Serializer) -> #krate::Result<()> {
+                        #statement
+                        Ok(())
+                    }
+
+                    fn serialize_borrowed_to<'derse, Serializer: #krate::BorrowingSerializer<'derse>>(&'derse self, serializer: &mut Serializer) -> #krate::Result<()> {
+                        #borrowed
+                        Ok(())
+                    }
+                }
+            });
+        }
+    }
+
+    let statements = serialize_statements(&krate, &container, &ast.data, false)?;
+    let borrowed = serialize_statements(&krate, &container, &ast.data, true)?;
+    Ok(quote! {
+        impl #impl_generics #krate::Serialize for #struct_type #ty_generics #where_clause {
+            fn serialize_to<Serializer: #krate::Serializer>(&self, serializer: &mut Serializer) -> #krate::Result<()> {
+                use #krate::Serialize;
+                let start = serializer.len();
+                #statements
+                let len = serializer.len() - start;
+                #krate::VarInt64(len as u64).serialize_to(serializer)
+            }
+
+            fn serialize_borrowed_to<'derse, Serializer: #krate::BorrowingSerializer<'derse>>(&'derse self, serializer: &mut Serializer) -> #krate::Result<()> {
+                use #krate::Serialize;
+                let start = serializer.len();
+                #borrowed
+                let len = serializer.len() - start;
+                #krate::VarInt64(len as u64).serialize_to(serializer)
+            }
+        }
+    })
+}
+
+/// Generates the statements serializing the fields or variants of a type, calling
+/// `serialize_borrowed_to` on them if `borrowed` is set.
+fn serialize_statements(
+    krate: &TokenStream,
+    container: &ContainerAttrs,
+    data: &Data,
+    borrowed: bool,
+) -> syn::Result<TokenStream> {
+    Ok(match data {
+        Data::Struct(DataStruct { fields, .. }) => {
+            let values = fields
+                .iter()
+                .enumerate()
+                .map(|(i, f)| {
+                    let member = field_member(i, f);
+                    quote! { &self.#member }
+                })
+                .collect::<Vec<_>>();
+            if container.tagged.is_some() {
+                serialize_tagged_fields(krate, fields, &values, borrowed)?
+            } else {
+                serialize_fields(krate, fields, &values, borrowed)?
+            }
+        }
+        Data::Enum(DataEnum { variants, .. }) => {
+            check_struct_only(container)?;
+            let tags = variant_tags(container, variants)?;
+            let other = other_variant(variants)?.filter(|variant| !variant.fields.is_empty());
+            let mut match_statements = Vec::new();
+            for (variant, tag) in variants.iter().zip(tags) {
+                let ident = &variant.ident;
+                let bindings = field_bindings(&variant.fields);
+                if other.is_some_and(|other| other.ident == variant.ident) {
+                    let tag = &bindings[0];
+                    let tag = match tag_kind(container, variants) {
+                        TagKind::Name => {
+                            quote! { #krate::Serialize::serialize_to(#tag, serializer)?; }
+                        }
+                        TagKind::Index => {
+                            quote! { #krate::VarInt64(*#tag).serialize_to(serializer)?; }
+                        }
+                    };
+                    let body = bindings.get(1).map(|body| {
+                        if borrowed {
+                            quote! { serializer.prepend_borrowed(::core::convert::AsRef::<[u8]>::as_ref(#body))?; }
+                        } else {
+                            quote! { serializer.prepend(#body)?; }
+                        }
+                    });
+                    let pattern = match &variant.fields {
+                        Fields::Named(_) => quote! { { #(#bindings, )* } },
+                        _ => quote! { ( #(#bindings, )* ) },
+                    };
+                    match_statements.push(quote! {
+                        Self::#ident #pattern => {
+                            #body
+                            #tag
+                        }
+                    });
+                    continue;
+                }
+
+                let tag = match tag {
+                    VariantTag::Name(name, _) => quote! { #name },
+                    VariantTag::Index(id) => quote! { #krate::VarInt64(#id) },
+                };
+                let values = bindings.iter().map(|b| quote! { #b }).collect::<Vec<_>>();
+                let statements = serialize_fields(krate, &variant.fields, &values, borrowed)?;
+                let pattern = variant_pattern(&variant.fields, &bindings)?;
+                match_statements.push(quote! {
+                    Self::#ident #pattern => {
+                        #statements
+                        #tag.serialize_to(serializer)?;
+                    }
+                });
+            }
+            quote! {
+                match self {
+                    #(#match_statements)*
+                }
+            }
+        }
+        Data::Union(DataUnion { union_token, .. }) => {
+            return Err(syn::Error::new_spanned(
+                union_token,
+                "only struct and enum are supported",
+            ));
+        }
+    })
+}
+
+/// Builds the pattern matching an enum variant, ignoring skipped fields.
+fn variant_pattern(fields: &Fields, bindings: &[syn::Ident]) -> syn::Result<TokenStream> {
+    let mut list = Vec::new();
+    for (f, binding) in fields.iter().zip(bindings) {
+        let skip = FieldAttrs::parse(&f.attrs)?.skip;
+        list.push(match (&f.ident, skip) {
+            (Some(ident), true) => quote! { #ident: _ },
+            (Some(ident), false) => quote! { #ident },
+            (None, true) => quote! { _ },
+            (None, false) => quote! { #binding },
+        });
+    }
+    Ok(match fields {
+        Fields::Named(_) => quote! { { #(#list, )* } },
+        Fields::Unnamed(_) => quote! { ( #(#list, )* ) },
+        Fields::Unit => quote! {},
+    })
+}
+
+/// Returns the statement serializing a reference to a field value.
+///
+/// Fields serialized with `with` or `varint` always go through `serialize_to`.
+fn serialize_value(
+    krate: &TokenStream,
+    attrs: &FieldAttrs,
+    value: &TokenStream,
+    borrowed: bool,
+) -> TokenStream {
+    match &attrs.with {
+        Some(with) => quote! { #with::serialize_to(#value, serializer)?; },
+        None if attrs.varint => quote! { #krate::varint::serialize_to(#value, serializer)?; },
+        None if borrowed => {
+            quote! { #krate::Serialize::serialize_borrowed_to(#value, serializer)?; }
+        }
+        None => quote! { #krate::Serialize::serialize_to(#value, serializer)?; },
+    }
+}
+
+/// Generates the statements serializing the given field values in reverse order.
+fn serialize_fields(
+    krate: &TokenStream,
+    fields: &Fields,
+    values: &[TokenStream],
+    borrowed: bool,
+) -> syn::Result<TokenStream> {
+    let mut statements = Vec::new();
+    for (f, value) in fields.iter().zip(values).rev() {
+        let attrs = FieldAttrs::parse(&f.attrs)?;
+        check_no_id(&attrs)?;
+        if !attrs.skip {
+            statements.push(serialize_value(krate, &attrs, value, borrowed));
+        }
+    }
+    Ok(quote! { #(#statements)* })
+}
+
+/// Generates the statements serializing the fields of a tagged struct in reverse order,
+/// each one as a `VarInt64` id, a `VarInt64` length and the value.
+fn serialize_tagged_fields(
+    krate: &TokenStream,
+    fields: &Fields,
+    values: &[TokenStream],
+    borrowed: bool,
+) -> syn::Result<TokenStream> {
+    let mut statements = Vec::new();
+    for ((attrs, id), value) in field_ids(fields)?.into_iter().zip(values).rev() {
+        let Some(id) = id else {
+            continue;
+        };
+        let statement = serialize_value(krate, &attrs, value, borrowed);
+        statements.push(quote! {
+            {
+                let field_start = serializer.len();
+                #statement
+                let field_len = serializer.len() - field_start;
+                #krate::VarInt64(field_len as u64).serialize_to(serializer)?;
+                #krate::VarInt64(#id).serialize_to(serializer)?;
+            }
+        });
+    }
+    Ok(quote! { #(#statements)* })
+}
@@ -0,0 +1,62 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use derse::{Deserialize, DownwardBytes, Serialize};
+
+/// A byte serialized one item at a time, as `u8` was before the bulk fast path.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[derse(transparent)]
+struct Byte(u8);
+
+/// A `u64` serialized one item at a time.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[derse(transparent)]
+struct Word(u64);
+
+const LEN: usize = 10 << 20;
+
+fn bench_bulk(c: &mut Criterion) {
+    let bytes: Vec<u8> = (0..LEN).map(|i| i as u8).collect();
+    let items: Vec<Byte> = bytes.iter().copied().map(Byte).collect();
+    let words: Vec<u64> = (0..LEN as u64 / 8).collect();
+    let word_items: Vec<Word> = words.iter().copied().map(Word).collect();
+
+    let mut group = c.benchmark_group("serialize");
+    group.throughput(Throughput::Bytes(LEN as u64));
+    group.bench_function("vec_u8", |b| {
+        b.iter(|| black_box(&bytes).serialize::<DownwardBytes>().unwrap())
+    });
+    group.bench_function("vec_u8_per_item", |b| {
+        b.iter(|| black_box(&items).serialize::<DownwardBytes>().unwrap())
+    });
+    group.bench_function("vec_u64", |b| {
+        b.iter(|| black_box(&words).serialize::<DownwardBytes>().unwrap())
+    });
+    group.bench_function("vec_u64_per_item", |b| {
+        b.iter(|| black_box(&word_items).serialize::<DownwardBytes>().unwrap())
+    });
+    group.finish();
+
+    let encoded = bytes.serialize::<DownwardBytes>().unwrap();
+    let encoded_words = words.serialize::<DownwardBytes>().unwrap();
+    let mut group = c.benchmark_group("deserialize");
+    group.throughput(Throughput::Bytes(LEN as u64));
+    group.bench_function("vec_u8", |b| {
+        b.iter(|| Vec::<u8>::deserialize(black_box(&encoded[..])).unwrap())
+    });
+    group.bench_function("vec_u8_per_item", |b| {
+        b.iter(|| Vec::<Byte>::deserialize(black_box(&encoded[..])).unwrap())
+    });
+    group.bench_function("vec_u64", |b| {
+        b.iter(|| Vec::<u64>::deserialize(black_box(&encoded_words[..])).unwrap())
+    });
+    group.bench_function("vec_u64_per_item", |b| {
+        b.iter(|| Vec::<Word>::deserialize(black_box(&encoded_words[..])).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = bench_bulk
+}
+criterion_main!(benches);
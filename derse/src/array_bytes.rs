@@ -0,0 +1,140 @@
+use super::slice_serializer::prepend_into;
+use super::{Result, Serializer};
+
+/// A fixed-capacity byte buffer on the stack, growing downward like `DownwardBytes`.
+///
+/// Prepending more than `N` bytes in total fails with `Error::BufferFull`, so values can be
+/// serialized without any allocation.
+#[derive(Clone)]
+pub struct ArrayBytes<const N: usize> {
+    buf: [u8; N],
+    offset: usize,
+}
+
+impl<const N: usize> ArrayBytes<N> {
+    /// Creates a new empty `ArrayBytes` instance.
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            offset: N,
+        }
+    }
+
+    /// Returns the length of the serialized data.
+    pub const fn len(&self) -> usize {
+        N - self.offset
+    }
+
+    /// Checks if the buffer is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the capacity of the buffer.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Clears the buffer.
+    pub fn clear(&mut self) {
+        self.offset = N;
+    }
+
+    /// Returns the buffer as a slice.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[self.offset..]
+    }
+
+    /// Prepends data to the buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data to prepend.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::BufferFull` if the data does not fit, leaving the buffer unchanged.
+    pub fn prepend(&mut self, data: impl AsRef<[u8]>) -> Result<()> {
+        prepend_into(&mut self.buf, &mut self.offset, data.as_ref())
+    }
+}
+
+impl<const N: usize> Default for ArrayBytes<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Serializer for ArrayBytes<N> {
+    /// Prepends data to the buffer, failing with `Error::BufferFull` if it does not fit.
+    fn prepend(&mut self, data: impl AsRef<[u8]>) -> Result<()> {
+        self.prepend(data)
+    }
+
+    /// Returns the length of the serialized data.
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<const N: usize> PartialEq for ArrayBytes<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<const N: usize> std::fmt::Debug for ArrayBytes<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ArrayBytes({:?})", self.as_slice())
+    }
+}
+
+impl<const N: usize> std::ops::Deref for ArrayBytes<N> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_array_bytes() {
+        let mut bytes = ArrayBytes::<8>::new();
+        assert!(bytes.is_empty());
+        assert_eq!(bytes.capacity(), 8);
+
+        bytes.prepend("world").unwrap();
+        bytes.prepend("").unwrap();
+        assert_eq!(bytes.as_slice(), b"world");
+        assert_eq!(
+            bytes.prepend("hello "),
+            Err(Error::BufferFull {
+                capacity: 8,
+                required: 11
+            })
+        );
+        assert_eq!(bytes.as_slice(), b"world");
+        bytes.prepend("lo ").unwrap();
+        assert_eq!(bytes.len(), 8);
+        assert_eq!(&bytes[..], b"lo world");
+
+        bytes.clear();
+        assert!(bytes.is_empty());
+
+        let ser = (233u32, "header".to_owned(), vec![1u16, 2, 3]);
+        let bytes = ser.serialize::<ArrayBytes<64>>().unwrap();
+        assert_eq!(bytes[..], ser.serialize::<DownwardBytes>().unwrap()[..]);
+        assert_eq!(
+            <(u32, String, Vec<u16>)>::deserialize(&bytes[..]).unwrap(),
+            ser
+        );
+        assert!(matches!(
+            ser.serialize::<ArrayBytes<8>>(),
+            Err(Error::BufferFull { capacity: 8, .. })
+        ));
+    }
+}
@@ -37,6 +37,11 @@ impl<'a> Deserializer<'a> for BytesArray<'a> {
         self.len == 0
     }
 
+    /// Returns the total length of the remaining byte slices.
+    fn remaining(&self) -> usize {
+        self.len
+    }
+
     /// Advances the `BytesArray` by the specified length.
     ///
     /// # Arguments
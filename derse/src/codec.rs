@@ -0,0 +1,208 @@
+use super::{DetailedDeserialize, DownwardBytes, Error, Result, Serialize};
+use bytes::BytesMut;
+use std::marker::PhantomData;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// A `tokio-util` codec for streams of derse messages.
+///
+/// Frames are delimited by the `VarInt64` length prefix that derived types already write,
+/// so a frame is exactly the serialized message and no extra header is added.
+pub struct DerseCodec<T> {
+    max_frame_len: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> DerseCodec<T> {
+    /// The default maximum length of a frame, 8 MiB.
+    pub const DEFAULT_MAX_FRAME_LEN: usize = 8 * 1024 * 1024;
+
+    /// The most buffer space reserved at once for a frame that has not fully arrived.
+    const MAX_RESERVE: usize = 64 * 1024;
+
+    /// Creates a new `DerseCodec` rejecting frames longer than `DEFAULT_MAX_FRAME_LEN`.
+    pub fn new() -> Self {
+        Self::with_max_frame_len(Self::DEFAULT_MAX_FRAME_LEN)
+    }
+
+    /// Creates a new `DerseCodec` rejecting frames longer than `max_frame_len` bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_frame_len` - The maximum length of a frame, excluding its length prefix.
+    pub fn with_max_frame_len(max_frame_len: usize) -> Self {
+        Self {
+            max_frame_len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the maximum length of a frame.
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+}
+
+impl<T> Default for DerseCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for DerseCodec<T> {
+    fn clone(&self) -> Self {
+        Self::with_max_frame_len(self.max_frame_len)
+    }
+}
+
+impl<T> std::fmt::Debug for DerseCodec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DerseCodec")
+            .field("max_frame_len", &self.max_frame_len)
+            .finish()
+    }
+}
+
+impl<T: Serialize> Encoder<T> for DerseCodec<T> {
+    type Error = Error;
+
+    /// Serializes the message and appends it to the buffer.
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<()> {
+        let len = item.serialize::<usize>()?;
+        let mut bytes = DownwardBytes::with_capacity(len);
+        item.serialize_to(&mut bytes)?;
+        dst.extend_from_slice(&bytes);
+        Ok(())
+    }
+}
+
+impl<T> Decoder for DerseCodec<T>
+where
+    T: for<'a> DetailedDeserialize<'a>,
+{
+    type Item = T;
+    type Error = Error;
+
+    /// Decodes a message once its whole frame is in the buffer.
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>> {
+        let mut buf = &src[..];
+        let len = match T::deserialize_len(&mut buf) {
+            Ok(len) => len,
+            Err(Error::DataIsShort { .. }) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        if len > self.max_frame_len {
+            return Err(Error::InvalidLength(
+                len,
+                format!("frame is longer than {}", self.max_frame_len),
+            ));
+        }
+
+        let header = src.len() - buf.len();
+        if buf.len() < len {
+            // The length is untrusted, so the buffer grows as the frame arrives instead.
+            src.reserve((len - buf.len()).min(Self::MAX_RESERVE));
+            return Ok(None);
+        }
+        let frame = src.split_to(header + len);
+        let mut buf = &frame[header..];
+        T::deserialize_fields(&mut buf).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Deserialize, Serialize};
+    use futures::{SinkExt, StreamExt};
+    use tokio_util::codec::{FramedRead, FramedWrite};
+
+    #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+    struct Message {
+        id: u64,
+        body: String,
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn test_partial_frames() {
+        let msg = Message {
+            id: 1,
+            body: "x".repeat(200),
+            tags: vec!["a".to_owned()],
+        };
+        let mut codec = DerseCodec::<Message>::new();
+        let mut encoded = BytesMut::new();
+        codec.encode(msg.clone(), &mut encoded).unwrap();
+        codec.encode(msg.clone(), &mut encoded).unwrap();
+        let frame_len = encoded.len() / 2;
+
+        let mut src = BytesMut::new();
+        for (i, byte) in encoded.iter().enumerate() {
+            src.extend_from_slice(&[*byte]);
+            let decoded = codec.decode(&mut src).unwrap();
+            if (i + 1) % frame_len == 0 {
+                assert_eq!(decoded.as_ref(), Some(&msg));
+                assert!(src.is_empty());
+            } else {
+                assert_eq!(decoded, None);
+            }
+        }
+
+        let mut codec = DerseCodec::<Message>::with_max_frame_len(100);
+        assert!(matches!(
+            codec.decode(&mut encoded),
+            Err(Error::InvalidLength(..))
+        ));
+    }
+
+    #[test]
+    fn test_huge_length_prefix() {
+        let header = crate::VarInt64(1 << 62)
+            .serialize::<DownwardBytes>()
+            .unwrap();
+
+        let mut codec = DerseCodec::<Message>::new();
+        let mut src = BytesMut::from(&header[..]);
+        assert!(matches!(
+            codec.decode(&mut src),
+            Err(Error::InvalidLength(..))
+        ));
+
+        let mut codec = DerseCodec::<Message>::with_max_frame_len(usize::MAX);
+        let mut src = BytesMut::from(&header[..]);
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+        assert!(src.capacity() <= header.len() + 64 * 1024);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_loopback() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let messages = (0..100)
+            .map(|i| Message {
+                id: i,
+                body: "hello derse!".repeat(i as usize),
+                tags: vec![i.to_string(); i as usize % 5],
+            })
+            .collect::<Vec<_>>();
+
+        let sent = messages.clone();
+        let client = tokio::spawn(async move {
+            let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+            let mut writer = FramedWrite::new(stream, DerseCodec::new());
+            for msg in sent {
+                writer.send(msg).await.unwrap();
+            }
+        });
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut reader = FramedRead::new(stream, DerseCodec::<Message>::new());
+        let mut received = Vec::new();
+        while let Some(msg) = reader.next().await {
+            received.push(msg.unwrap());
+        }
+        client.await.unwrap();
+        assert_eq!(received, messages);
+    }
+}
@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 
-use super::{Error, Result};
+use super::{Error, Result, SharedPointers};
 
 /// A trait for deserializing data from a byte slice.
 pub trait Deserializer<'a> {
@@ -22,6 +22,79 @@ pub trait Deserializer<'a> {
     ///
     /// Returns an error if the length to pop exceeds the available data.
     fn pop(&mut self, len: usize) -> Result<Cow<'a, [u8]>>;
+
+    /// Pops the specified length of data from the deserializer as `bytes::Bytes`.
+    ///
+    /// Deserializers reading from a `Bytes` buffer return a sub-slice of it without copying.
+    /// The default implementation converts the result of `pop`, copying borrowed data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length to pop exceeds the available data.
+    #[cfg(feature = "bytes")]
+    fn pop_bytes(&mut self, len: usize) -> Result<bytes::Bytes> {
+        Ok(match self.pop(len)? {
+            Cow::Borrowed(borrowed) => bytes::Bytes::copy_from_slice(borrowed),
+            Cow::Owned(owned) => owned.into(),
+        })
+    }
+
+    /// Returns the number of bytes left.
+    ///
+    /// It is only used to compute the offsets reported by `Error::Context`, so a deserializer
+    /// of unknown length may return any value that decreases by the number of bytes consumed.
+    /// The default implementation returns 0, which reports every offset as 0.
+    fn remaining(&self) -> usize {
+        0
+    }
+
+    /// Checks the length of a collection before its items are deserialized.
+    ///
+    /// Collection impls call this with the decoded item count and the size of an item,
+    /// so that a deserializer can reject lengths above its limits. The default
+    /// implementation accepts any length.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::LimitExceeded` if the collection is too large.
+    fn check_collection_len(&mut self, _len: usize, _item_size: usize) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks the length of a byte sequence, such as a string, before it is popped.
+    ///
+    /// The default implementation accepts any length.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::LimitExceeded` if the byte sequence is too large.
+    fn check_bytes_len(&mut self, _len: usize) -> Result<()> {
+        Ok(())
+    }
+
+    /// Enters a nested value, such as a derived type or a collection.
+    ///
+    /// Derived and collection impls call this before deserializing their contents and
+    /// `leave_nested` afterwards, so that a deserializer can bound the recursion depth.
+    /// The default implementation accepts any depth.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::LimitExceeded` if the value is nested too deeply.
+    fn enter_nested(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Leaves a nested value entered with `enter_nested`.
+    fn leave_nested(&mut self) {}
+
+    /// Returns the shared pointers decoded so far, if the deserializer rebuilds them.
+    ///
+    /// `Shared` pointers read an id before their value when this returns `Some`, matching the
+    /// format of a `SharedSerializer`. The default implementation returns `None`.
+    fn shared_pointers(&self) -> Option<&SharedPointers> {
+        None
+    }
 }
 
 /// Implements the `Deserializer` trait for a byte slice.
@@ -52,6 +125,11 @@ impl<'a> Deserializer<'a> for &'a [u8] {
         }
     }
 
+    /// Returns the length of the byte slice.
+    fn remaining(&self) -> usize {
+        self.len()
+    }
+
     /// Pops the specified length of data from the byte slice.
     ///
     /// # Errors
@@ -1,8 +1,15 @@
 use super::{Result, Serializer};
+use std::mem::MaybeUninit;
 
 /// A struct for managing a downward-growing byte buffer.
+///
+/// Data is written from the end of the buffer towards its start, so `buf[offset..]` is
+/// initialized and everything before it may not be.
 #[derive(Default)]
-pub struct DownwardBytes(Vec<u8>);
+pub struct DownwardBytes {
+    buf: Box<[MaybeUninit<u8>]>,
+    offset: usize,
+}
 
 impl DownwardBytes {
     /// Creates a new `DownwardBytes` instance.
@@ -12,17 +19,15 @@ impl DownwardBytes {
 
     /// Creates a new `DownwardBytes` instance with the specified capacity.
     pub fn with_capacity(cap: usize) -> Self {
-        Self(Self::new_vec(cap, cap))
-    }
-
-    /// Returns the current offset in the buffer.
-    fn offset(&self) -> usize {
-        self.0.len()
+        Self {
+            buf: Box::new_uninit_slice(cap),
+            offset: cap,
+        }
     }
 
     /// Returns the length of the serialized data.
     pub fn len(&self) -> usize {
-        self.capacity() - self.offset()
+        self.capacity() - self.offset
     }
 
     /// Checks if the buffer is empty.
@@ -32,18 +37,18 @@ impl DownwardBytes {
 
     /// Returns the capacity of the buffer.
     pub fn capacity(&self) -> usize {
-        self.0.capacity()
+        self.buf.len()
     }
 
     /// Clears the buffer.
     pub fn clear(&mut self) {
-        unsafe { self.0.set_len(self.capacity()) };
+        self.offset = self.capacity();
     }
 
     /// Clears the buffer and shrinks its capacity to the specified size.
     pub fn clear_and_shrink_to(&mut self, capacity: usize) {
         if self.capacity() <= capacity {
-            unsafe { self.0.set_len(self.capacity()) };
+            self.clear();
         } else {
             *self = Self::with_capacity(capacity);
         }
@@ -51,44 +56,52 @@ impl DownwardBytes {
 
     /// Returns the buffer as a slice.
     pub fn as_slice(&self) -> &[u8] {
-        unsafe { std::slice::from_raw_parts(self.0.as_ptr().byte_add(self.offset()), self.len()) }
-    }
-
-    /// Returns the buffer as a mutable slice.
-    fn as_mut_slice(&mut self) -> &mut [u8] {
-        unsafe {
-            std::slice::from_raw_parts_mut(self.0.as_mut_ptr().byte_add(self.offset()), self.len())
-        }
+        let data = &self.buf[self.offset..];
+        // SAFETY: `buf[offset..]` is initialized, and `MaybeUninit<u8>` has the layout of `u8`.
+        unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, data.len()) }
     }
 
     /// Prepends data to the buffer.
     pub fn prepend(&mut self, data: impl AsRef<[u8]>) {
         let buf = data.as_ref();
-        if self.offset() < buf.len() {
+        if self.offset < buf.len() {
             self.reserve(self.len() + buf.len());
         }
 
-        let new_offset = self.offset() - buf.len();
-        self.0[new_offset..].copy_from_slice(buf);
-        self.0.truncate(new_offset)
+        let new_offset = self.offset - buf.len();
+        let dst = &mut self.buf[new_offset..self.offset];
+        // SAFETY: `dst` and `buf` have the same length and cannot overlap, since `dst` is
+        // owned by `self`.
+        unsafe {
+            std::ptr::copy_nonoverlapping(buf.as_ptr(), dst.as_mut_ptr() as *mut u8, buf.len())
+        };
+        self.offset = new_offset;
     }
 
     /// Reserves space for the specified size.
     pub fn reserve(&mut self, size: usize) {
         if self.capacity() < size {
             let new_cap = std::cmp::max(self.capacity() * 2, size);
-            let mut new_bytes = Self(Self::new_vec(new_cap, new_cap - self.len()));
-            new_bytes.as_mut_slice().copy_from_slice(self.as_ref());
-            self.0 = new_bytes.0;
+            let mut new_bytes = Self::with_capacity(new_cap);
+            new_bytes.prepend(self.as_slice());
+            *self = new_bytes;
         }
     }
 
-    /// Creates a new vector with the specified capacity and length.
-    #[allow(clippy::uninit_vec)]
-    fn new_vec(cap: usize, len: usize) -> Vec<u8> {
-        let mut vec = Vec::with_capacity(cap);
-        unsafe { vec.set_len(len) };
-        vec
+    /// Converts the buffer into a `Vec<u8>` of the serialized data.
+    ///
+    /// The allocation is reused, so the data is not copied if the buffer is full, as it is
+    /// after serializing into `with_capacity` of the exact size. Otherwise the data is moved
+    /// to the start of the allocation.
+    pub fn into_vec(self) -> Vec<u8> {
+        let (len, cap, offset) = (self.len(), self.capacity(), self.offset);
+        let ptr = Box::into_raw(self.buf) as *mut u8;
+        // SAFETY: the allocation holds `cap` bytes with the layout of a `Vec<u8>` of that
+        // capacity, and its first `len` bytes are initialized after moving the data.
+        unsafe {
+            std::ptr::copy(ptr.add(offset), ptr, len);
+            Vec::from_raw_parts(ptr, len, cap)
+        }
     }
 }
 
@@ -117,6 +130,20 @@ impl std::fmt::Debug for DownwardBytes {
     }
 }
 
+impl From<DownwardBytes> for Vec<u8> {
+    fn from(bytes: DownwardBytes) -> Self {
+        bytes.into_vec()
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl From<DownwardBytes> for bytes::Bytes {
+    /// Converts the buffer into `Bytes`, reusing its allocation as `into_vec` does.
+    fn from(bytes: DownwardBytes) -> Self {
+        bytes.into_vec().into()
+    }
+}
+
 impl std::ops::Deref for DownwardBytes {
     type Target = [u8];
 
@@ -144,6 +171,43 @@ mod tests {
         bytes.prepend("hello ");
         assert_eq!(bytes.len(), 12);
         assert_eq!(bytes.as_slice(), b"hello world!");
+
+        bytes.prepend("");
+        assert_eq!(bytes.as_slice(), b"hello world!");
+        bytes.prepend("> ");
+        assert_eq!(bytes.capacity(), 16);
+        assert_eq!(bytes.as_slice(), b"> hello world!");
+    }
+
+    #[test]
+    fn test_downward_bytes_into_vec() {
+        assert_eq!(DownwardBytes::new().into_vec(), Vec::<u8>::new());
+
+        let mut bytes = DownwardBytes::with_capacity(5);
+        bytes.prepend("llo");
+        bytes.prepend("he");
+        let ptr = bytes.as_slice().as_ptr();
+        let vec = bytes.into_vec();
+        assert_eq!(vec, b"hello");
+        assert_eq!(vec.as_ptr(), ptr);
+        assert_eq!(vec.capacity(), 5);
+
+        let mut bytes = DownwardBytes::with_capacity(64);
+        bytes.prepend("world");
+        bytes.prepend("hello ");
+        let mut vec: Vec<u8> = bytes.into();
+        assert_eq!(vec, b"hello world");
+        assert_eq!(vec.capacity(), 64);
+        vec.extend_from_slice(b"!");
+        assert_eq!(vec, b"hello world!");
+
+        let mut bytes = DownwardBytes::new();
+        for i in 0..100u8 {
+            bytes.prepend([i; 3]);
+        }
+        bytes.clear();
+        bytes.prepend("reused");
+        assert_eq!(bytes.into_vec(), b"reused");
     }
 
     #[test]
@@ -154,7 +218,7 @@ mod tests {
         assert_eq!(bytes.capacity(), 0);
         assert_eq!(format!("{:?}", bytes), "DownwardBytes([])");
 
-        const N: usize = 100000;
+        const N: usize = if cfg!(miri) { 1000 } else { 100000 };
         for i in 0..N {
             bytes.prepend([i as u8]);
         }
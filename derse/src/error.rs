@@ -1,7 +1,8 @@
 use crate as derse;
 
-#[derive(thiserror::Error, derse::Serialize, derse::Deserialize, PartialEq, Clone)]
+#[derive(thiserror::Error, derse::Serialize, derse::Deserialize, PartialEq, Clone, Default)]
 pub enum Error {
+    #[default]
     #[error("default")]
     Default,
     #[error("data is short for deserialize: expect {expect}, actual {actual}")]
@@ -22,11 +23,164 @@ pub enum Error {
     InvalidChar(u32),
     #[error("invalid length: {0}, error: {1}")]
     InvalidLength(usize, String),
+    #[error("io error: {0}")]
+    IoError(String),
+    #[error("limit exceeded: {0}")]
+    LimitExceeded(String),
+    #[error("buffer is full: capacity {capacity}, required {required}")]
+    BufferFull { capacity: usize, required: usize },
+    /// The error happened while deserializing a derived type or a collection.
+    ///
+    /// Errors from derived types are always wrapped in this variant, so matching on the
+    /// original variant, such as `Error::DataIsShort`, requires `root_cause()`.
+    #[error(transparent)]
+    Context(Box<ErrorContext>),
 }
 
-impl Default for Error {
-    fn default() -> Self {
-        Self::Default
+/// The location of an error, carried by `Error::Context`.
+#[derive(thiserror::Error, derse::Serialize, derse::Deserialize, Clone, Default)]
+#[error("{ty}{path} at offset {offset}: {error}")]
+pub struct ErrorContext {
+    /// The outermost derived type the error happened in, if the path starts at one.
+    pub ty: String,
+    /// The path from `ty` to the failing value, such as `.items[3].name`.
+    pub path: String,
+    /// The offset of the error from the start of `ty`, or of the input for `deserialize`.
+    pub offset: usize,
+    /// The number of bytes after the error, used to compute `offset`.
+    #[derse(skip)]
+    remaining: usize,
+    /// The error without context.
+    #[source]
+    pub error: Error,
+}
+
+impl PartialEq for ErrorContext {
+    fn eq(&self, other: &Self) -> bool {
+        self.ty == other.ty
+            && self.path == other.path
+            && self.offset == other.offset
+            && self.error == other.error
+    }
+}
+
+impl std::fmt::Debug for ErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self, f)
+    }
+}
+
+impl Error {
+    /// Wraps the error in `Error::Context`, unless it already carries a context.
+    ///
+    /// # Arguments
+    ///
+    /// * `remaining` - The number of bytes left in the deserializer where the error happened.
+    pub fn context(self, remaining: usize) -> Self {
+        match self {
+            Self::Context(_) => self,
+            error => Self::Context(Box::new(ErrorContext {
+                ty: String::new(),
+                path: String::new(),
+                offset: 0,
+                remaining,
+                error,
+            })),
+        }
+    }
+
+    /// Prepends a field name to the path of the error.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the field being deserialized.
+    /// * `remaining` - The number of bytes left in the deserializer of the field.
+    pub fn with_field(self, name: &str, remaining: usize) -> Self {
+        self.with_segment(format!(".{}", name), remaining)
+    }
+
+    /// Prepends a collection index to the path of the error.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the item being deserialized.
+    /// * `remaining` - The number of bytes left in the deserializer of the collection.
+    pub fn with_index(self, index: usize, remaining: usize) -> Self {
+        self.with_segment(format!("[{}]", index), remaining)
+    }
+
+    /// Accounts for the bytes following a deserializer returned by `advance`.
+    ///
+    /// # Arguments
+    ///
+    /// * `remaining` - The number of bytes left in the deserializer `advance` was called on.
+    pub fn advanced(self, remaining: usize) -> Self {
+        match self {
+            Self::Context(mut context) => {
+                context.remaining = context.remaining.saturating_add(remaining);
+                Self::Context(context)
+            }
+            error => error.context(remaining),
+        }
+    }
+
+    /// Records that the error happened in a length-prefixed value of the given type.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the type.
+    /// * `start` - The number of bytes left before the value was deserialized.
+    /// * `remaining` - The number of bytes left after the value was split off with `advance`.
+    pub fn with_type(self, name: &str, start: usize, remaining: usize) -> Self {
+        match self.advanced(remaining) {
+            Self::Context(mut context) => {
+                context.ty = name.to_owned();
+                context.offset = start.saturating_sub(context.remaining);
+                Self::Context(context)
+            }
+            error => error,
+        }
+    }
+
+    /// Computes the offset of the error from the start of the input.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The number of bytes in the input.
+    pub(crate) fn with_start(self, start: usize) -> Self {
+        match self {
+            Self::Context(mut context) => {
+                context.offset = start.saturating_sub(context.remaining);
+                Self::Context(context)
+            }
+            error => error,
+        }
+    }
+
+    /// Returns the error without context.
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Self::Context(context) => context.error.root_cause(),
+            error => error,
+        }
+    }
+
+    /// Prepends a segment to the path of the error, dropping the type it started at.
+    fn with_segment(self, segment: String, remaining: usize) -> Self {
+        match self.context(remaining) {
+            Self::Context(mut context) => {
+                context.ty.clear();
+                context.path.insert_str(0, &segment);
+                Self::Context(context)
+            }
+            error => error,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Self::IoError(e.to_string())
     }
 }
 
@@ -58,5 +212,22 @@ mod tests {
         assert_eq!(ser, der);
 
         let _ = Error::default().serialize::<DownwardBytes>().unwrap();
+
+        let ser = Error::InvalidBool(2)
+            .with_field("flag", 3)
+            .with_type("Inner", 10, 4)
+            .with_index(1, 20)
+            .with_field("inners", 20)
+            .with_type("Outer", 30, 5);
+        assert_eq!(
+            ser.to_string(),
+            "Outer.inners[1].flag at offset 18: invalid bool: 2"
+        );
+        assert_eq!(ser.root_cause(), &Error::InvalidBool(2));
+        let bytes = ser.serialize::<DownwardBytes>().unwrap();
+        let der = Error::deserialize(&bytes[..]).unwrap();
+        assert_eq!(ser, der);
+        assert_eq!(der.to_string(), ser.to_string());
+        assert!(std::mem::size_of::<Error>() <= 4 * std::mem::size_of::<usize>());
     }
 }
@@ -2,10 +2,14 @@ use crate::*;
 
 impl<T: Serialize, const N: usize> Serialize for [T; N] {
     fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
-        for item in self.iter().rev() {
-            item.serialize_to(serializer)?;
-        }
-        Ok(())
+        T::serialize_slice_to(self, serializer)
+    }
+
+    fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+        &'a self,
+        serializer: &mut S,
+    ) -> Result<()> {
+        T::serialize_slice_borrowed_to(self, serializer)
     }
 }
 
@@ -0,0 +1,129 @@
+use crate::*;
+use bytes::Bytes;
+use std::borrow::Cow;
+
+impl Serialize for Bytes {
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
+        serializer.prepend_bytes(self)?;
+        VarInt64(self.len() as u64).serialize_to(serializer)
+    }
+}
+
+impl<'a> Deserialize<'a> for Bytes {
+    fn deserialize_from<D: Deserializer<'a>>(buf: &mut D) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let len = VarInt64::deserialize_from(buf)?.0 as usize;
+        buf.check_bytes_len(len)?;
+        buf.pop_bytes(len)
+    }
+}
+
+/// Implements the `Deserializer` trait for `Bytes`.
+///
+/// Popped `Bytes` share the buffer of the input, while other popped data is copied, since
+/// it cannot borrow from a buffer that is not `'static`.
+impl Deserializer<'static> for Bytes {
+    /// Checks if the buffer is empty.
+    fn is_empty(&self) -> bool {
+        Bytes::is_empty(self)
+    }
+
+    /// Advances the buffer by the specified length, returning the skipped part.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length to advance exceeds the available data.
+    fn advance(&mut self, len: usize) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        self.pop_bytes(len)
+    }
+
+    /// Returns the length of the buffer.
+    fn remaining(&self) -> usize {
+        self.len()
+    }
+
+    /// Pops the specified length of data from the buffer, copying it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length to pop exceeds the available data.
+    fn pop(&mut self, len: usize) -> Result<Cow<'static, [u8]>> {
+        Ok(Cow::Owned(self.pop_bytes(len)?.into()))
+    }
+
+    /// Pops the specified length of data from the buffer without copying.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length to pop exceeds the available data.
+    fn pop_bytes(&mut self, len: usize) -> Result<Bytes> {
+        if len <= self.len() {
+            Ok(self.split_to(len))
+        } else {
+            Err(Error::DataIsShort {
+                expect: len,
+                actual: self.len(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Frame {
+        id: u32,
+        name: String,
+        payload: Bytes,
+    }
+
+    #[test]
+    fn test_bytes() {
+        let ser = Frame {
+            id: 233,
+            name: "frame".to_owned(),
+            payload: Bytes::from_static(b"payload"),
+        };
+        let bytes = ser.serialize::<DownwardBytes>().unwrap();
+        assert_eq!(Frame::deserialize(&bytes[..]).unwrap(), ser);
+
+        let input = Bytes::from(bytes);
+        let range = input.as_ptr_range();
+        let der = Frame::deserialize(input.clone()).unwrap();
+        assert_eq!(der, ser);
+        assert!(range.contains(&der.payload.as_ptr()));
+
+        let bytes = b"payload"[..].serialize::<DownwardBytes>().unwrap();
+        assert_eq!(ser.payload.serialize::<DownwardBytes>().unwrap(), bytes);
+        assert_eq!(
+            Vec::<u8>::deserialize(Bytes::from(bytes)).unwrap(),
+            b"payload"
+        );
+
+        let mut input = input.slice(..3);
+        assert_eq!(
+            input.advance(4),
+            Err(Error::DataIsShort {
+                expect: 4,
+                actual: 3
+            })
+        );
+        assert_eq!(input.advance(1).unwrap().len(), 1);
+        assert_eq!(input.len(), 2);
+
+        let len = ser.serialize::<usize>().unwrap();
+        let mut bytes = DownwardBytes::with_capacity(len);
+        ser.serialize_to(&mut bytes).unwrap();
+        let ptr = bytes.as_ptr();
+        let bytes = Bytes::from(bytes);
+        assert_eq!(bytes.as_ptr(), ptr);
+        assert_eq!(Frame::deserialize(bytes).unwrap(), ser);
+    }
+}
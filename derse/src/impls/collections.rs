@@ -3,6 +3,69 @@ use std::cmp::Eq;
 use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
 use std::hash::Hash;
 
+impl<T: Serialize> Serialize for Vec<T> {
+    #[inline]
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
+        T::serialize_slice_to(self, serializer)?;
+        VarInt64(self.len() as u64).serialize_to(serializer)
+    }
+
+    #[inline]
+    fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+        &'a self,
+        serializer: &mut S,
+    ) -> Result<()> {
+        T::serialize_slice_borrowed_to(self, serializer)?;
+        VarInt64(self.len() as u64).serialize_to(serializer)
+    }
+}
+
+impl<'a, T: Deserialize<'a>> Deserialize<'a> for Vec<T> {
+    #[inline]
+    fn deserialize_from<D: Deserializer<'a>>(buf: &mut D) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let len = VarInt64::deserialize_from(buf)?.0 as usize;
+        buf.check_collection_len(len, std::mem::size_of::<T>())?;
+        buf.enter_nested()?;
+        let result = T::deserialize_vec_from(buf, len);
+        buf.leave_nested();
+        result
+    }
+}
+
+impl<T: Serialize> Serialize for VecDeque<T> {
+    #[inline]
+    fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
+        let (front, back) = self.as_slices();
+        T::serialize_slice_to(back, serializer)?;
+        T::serialize_slice_to(front, serializer)?;
+        VarInt64(self.len() as u64).serialize_to(serializer)
+    }
+
+    #[inline]
+    fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+        &'a self,
+        serializer: &mut S,
+    ) -> Result<()> {
+        let (front, back) = self.as_slices();
+        T::serialize_slice_borrowed_to(back, serializer)?;
+        T::serialize_slice_borrowed_to(front, serializer)?;
+        VarInt64(self.len() as u64).serialize_to(serializer)
+    }
+}
+
+impl<'a, T: Deserialize<'a>> Deserialize<'a> for VecDeque<T> {
+    #[inline]
+    fn deserialize_from<D: Deserializer<'a>>(buf: &mut D) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        Vec::<T>::deserialize_from(buf).map(Into::into)
+    }
+}
+
 macro_rules! seq_se_rev_impl {
     (
         $ty:ident <T $(: $tbound1:ident $(+ $tbound2:ident)*)*>
@@ -22,14 +85,6 @@ macro_rules! seq_se_rev_impl {
     };
 }
 
-seq_se_rev_impl! {
-    Vec<T>
-}
-
-seq_se_rev_impl! {
-    VecDeque<T>
-}
-
 seq_se_rev_impl! {
     LinkedList<T>
 }
@@ -78,20 +133,18 @@ macro_rules! seq_de_impl {
                 Self: Sized,
             {
                 let len = VarInt64::deserialize_from(buf)?.0 as usize;
-                (0..len).map(|_| T::deserialize_from(buf)).collect::<Result<Self>>()
+                buf.check_collection_len(len, std::mem::size_of::<T>())?;
+                buf.enter_nested()?;
+                let result = (0..len)
+                    .map(|i| T::deserialize_from(buf).map_err(|e| e.with_index(i, buf.remaining())))
+                    .collect::<Result<Self>>();
+                buf.leave_nested();
+                result
             }
         }
     };
 }
 
-seq_de_impl! {
-    Vec<T>
-}
-
-seq_de_impl! {
-    VecDeque<T>
-}
-
 seq_de_impl! {
     LinkedList<T>
 }
@@ -138,7 +191,15 @@ macro_rules! map_impl {
                 Self: Sized,
             {
                 let len = VarInt64::deserialize_from(buf)?.0 as usize;
-                (0..len).map(|_| <(K, V)>::deserialize_from(buf)).collect::<Result<Self>>()
+                buf.check_collection_len(len, std::mem::size_of::<(K, V)>())?;
+                buf.enter_nested()?;
+                let result = (0..len)
+                    .map(|i| {
+                        <(K, V)>::deserialize_from(buf).map_err(|e| e.with_index(i, buf.remaining()))
+                    })
+                    .collect::<Result<Self>>();
+                buf.leave_nested();
+                result
             }
         }
     }
@@ -156,6 +217,40 @@ map_impl! {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_bulk() {
+        let ser: Vec<u32> = (0..1000).map(|i| i * 7919).collect();
+        let bytes: DownwardBytes = ser.serialize().unwrap();
+        let list: LinkedList<u32> = ser.iter().copied().collect();
+        assert_eq!(bytes[..], list.serialize::<DownwardBytes>().unwrap()[..]);
+        assert_eq!(Vec::<u32>::deserialize(&bytes[..]).unwrap(), ser);
+        assert_eq!(LinkedList::<u32>::deserialize(&bytes[..]).unwrap(), list);
+        assert!(Vec::<u32>::deserialize(&bytes[..bytes.len() - 1]).is_err());
+
+        let ser: Vec<u8> = (0..=255).collect();
+        let bytes: DownwardBytes = ser.serialize().unwrap();
+        assert_eq!(
+            bytes[..],
+            ser.as_slice().serialize::<DownwardBytes>().unwrap()[..]
+        );
+        assert_eq!(Vec::<u8>::deserialize(&bytes[..]).unwrap(), ser);
+
+        let mut ser: VecDeque<f64> = (0..5).map(|i| i as f64 / 3.0).collect();
+        for i in 1..5 {
+            ser.push_front(-i as f64);
+        }
+        assert!(!ser.as_slices().1.is_empty());
+        let bytes: DownwardBytes = ser.serialize().unwrap();
+        let vec: Vec<f64> = ser.iter().copied().collect();
+        assert_eq!(bytes[..], vec.serialize::<DownwardBytes>().unwrap()[..]);
+        assert_eq!(VecDeque::<f64>::deserialize(&bytes[..]).unwrap(), ser);
+
+        let ser = [i16::MIN, -1, 0, 1, i16::MAX];
+        let bytes: DownwardBytes = ser.serialize().unwrap();
+        assert_eq!(bytes.len(), 10);
+        assert_eq!(<[i16; 5]>::deserialize(&bytes[..]).unwrap(), ser);
+    }
+
     #[test]
     fn test_collections() {
         {
@@ -5,6 +5,13 @@ impl Serialize for Cow<'_, [u8]> {
     fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
         <[u8]>::serialize_to(self, serializer)
     }
+
+    fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+        &'a self,
+        serializer: &mut S,
+    ) -> Result<()> {
+        <[u8]>::serialize_borrowed_to(self, serializer)
+    }
 }
 
 impl<'a> Deserialize<'a> for Cow<'a, [u8]> {
@@ -13,6 +20,7 @@ impl<'a> Deserialize<'a> for Cow<'a, [u8]> {
         Self: Sized,
     {
         let len = VarInt64::deserialize_from(buf)?.0 as usize;
+        buf.check_bytes_len(len)?;
         buf.pop(len)
     }
 }
@@ -21,6 +29,13 @@ impl Serialize for Cow<'_, str> {
     fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
         self.as_ref().serialize_to(serializer)
     }
+
+    fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+        &'a self,
+        serializer: &mut S,
+    ) -> Result<()> {
+        str::serialize_borrowed_to(self, serializer)
+    }
 }
 
 impl<'a> Deserialize<'a> for Cow<'a, str> {
@@ -29,6 +44,7 @@ impl<'a> Deserialize<'a> for Cow<'a, str> {
         Self: Sized,
     {
         let len = VarInt64::deserialize_from(buf)?.0 as usize;
+        buf.check_bytes_len(len)?;
         let front = buf.pop(len)?;
         match front {
             Cow::Borrowed(borrowed) => match std::str::from_utf8(borrowed) {
@@ -39,7 +39,7 @@ mod tests {
 
     #[test]
     fn test_os_str() {
-        let ser = CStr::from_bytes_with_nul(b"hello\0").unwrap();
+        let ser = c"hello";
         assert_eq!(ser.count_bytes(), 5);
         let bytes = ser.serialize::<DownwardBytes>().unwrap();
         assert_eq!(bytes.len(), 7);
@@ -6,12 +6,15 @@ mod duration;
 mod osstr;
 mod pathbuf;
 mod phantom_data;
+mod pointer;
 mod primitive;
 mod result;
 mod socket_addr;
 mod string;
 mod tuple;
 
+#[cfg(feature = "bytes")]
+mod bytes;
 #[cfg(feature = "compact_str")]
 mod compact_str;
 #[cfg(feature = "tinyvec")]
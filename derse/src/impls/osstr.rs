@@ -17,6 +17,7 @@ impl<'a> Deserialize<'a> for &'a OsStr {
         Self: Sized,
     {
         let len = VarInt64::deserialize_from(buf)?.0 as usize;
+        buf.check_bytes_len(len)?;
         let front = buf.pop(len)?;
         match front {
             Cow::Borrowed(borrowed) => Ok(OsStr::from_bytes(borrowed)),
@@ -37,6 +38,7 @@ impl<'a> Deserialize<'a> for OsString {
         Self: Sized,
     {
         let len = VarInt64::deserialize_from(buf)?.0 as usize;
+        buf.check_bytes_len(len)?;
         let front = buf.pop(len)?;
         Ok(OsString::from_vec(front.into_owned()))
     }
@@ -61,8 +63,8 @@ mod tests {
         assert_eq!(ser, der);
 
         let msg = "0".repeat(47) + "A";
-        let a = msg[..25].as_bytes();
-        let b = msg[24..].as_bytes();
+        let a = &msg.as_bytes()[..25];
+        let b = &msg.as_bytes()[24..];
         let c = [a, b];
         <&OsStr>::deserialize(BytesArray::new(&c)).unwrap_err();
     }
@@ -0,0 +1,164 @@
+use crate::shared::{deserialize_shared, serialize_shared};
+use crate::*;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Implements `Box`, `Rc` and `Arc`, written exactly like the value they point to.
+macro_rules! pointer_impl {
+    ($ty:ident) => {
+        impl<T: Serialize> Serialize for $ty<T> {
+            #[inline]
+            fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
+                T::serialize_to(self, serializer)
+            }
+
+            #[inline]
+            fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+                &'a self,
+                serializer: &mut S,
+            ) -> Result<()> {
+                T::serialize_borrowed_to(self, serializer)
+            }
+        }
+
+        impl<'a, T: Deserialize<'a>> Deserialize<'a> for $ty<T> {
+            #[inline]
+            fn deserialize_from<D: Deserializer<'a>>(buf: &mut D) -> Result<Self>
+            where
+                Self: Sized,
+            {
+                T::deserialize_from(buf).map($ty::new)
+            }
+        }
+
+        impl Serialize for $ty<str> {
+            #[inline]
+            fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
+                str::serialize_to(self, serializer)
+            }
+
+            #[inline]
+            fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+                &'a self,
+                serializer: &mut S,
+            ) -> Result<()> {
+                str::serialize_borrowed_to(self, serializer)
+            }
+        }
+
+        impl<'a> Deserialize<'a> for $ty<str> {
+            #[inline]
+            fn deserialize_from<D: Deserializer<'a>>(buf: &mut D) -> Result<Self>
+            where
+                Self: Sized,
+            {
+                String::deserialize_from(buf).map(Into::into)
+            }
+        }
+
+        impl<T: Serialize> Serialize for $ty<[T]> {
+            #[inline]
+            fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
+                T::serialize_slice_to(self, serializer)?;
+                VarInt64(self.len() as u64).serialize_to(serializer)
+            }
+
+            #[inline]
+            fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+                &'a self,
+                serializer: &mut S,
+            ) -> Result<()> {
+                T::serialize_slice_borrowed_to(self, serializer)?;
+                VarInt64(self.len() as u64).serialize_to(serializer)
+            }
+        }
+
+        impl<'a, T: Deserialize<'a>> Deserialize<'a> for $ty<[T]> {
+            #[inline]
+            fn deserialize_from<D: Deserializer<'a>>(buf: &mut D) -> Result<Self>
+            where
+                Self: Sized,
+            {
+                Vec::<T>::deserialize_from(buf).map(Into::into)
+            }
+        }
+    };
+}
+
+pointer_impl!(Box);
+pointer_impl!(Rc);
+pointer_impl!(Arc);
+
+/// Implements `Shared<Rc<_>>` and `Shared<Arc<_>>`, deduplicated by a `SharedSerializer` and
+/// rebuilt by a `SharedDeserializer`. Decoded pointers are kept as `dyn Any`, so the pointee
+/// must be `'static`.
+macro_rules! shared_pointer_impl {
+    ($ty:ident, $($pointee:ty => [$($generics:tt)*]),*) => {
+        $(impl<$($generics)*> Serialize for Shared<$ty<$pointee>>
+        where
+            $ty<$pointee>: Serialize,
+        {
+            #[inline]
+            fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
+                let addr = $ty::as_ptr(&self.0) as *const () as usize;
+                serialize_shared(addr, serializer, |serializer| self.0.serialize_to(serializer))
+            }
+        }
+
+        impl<'a, $($generics)*> Deserialize<'a> for Shared<$ty<$pointee>>
+        where
+            $ty<$pointee>: Deserialize<'a> + 'static,
+        {
+            #[inline]
+            fn deserialize_from<D: Deserializer<'a>>(buf: &mut D) -> Result<Self>
+            where
+                Self: Sized,
+            {
+                deserialize_shared(buf, |buf| $ty::<$pointee>::deserialize_from(buf)).map(Shared)
+            }
+        })*
+    };
+}
+
+shared_pointer_impl!(Rc, T => [T], str => [], [T] => [T]);
+shared_pointer_impl!(Arc, T => [T], str => [], [T] => [T]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pointer() {
+        let ser = Box::new(233u32);
+        let bytes = ser.serialize::<DownwardBytes>().unwrap();
+        assert_eq!(bytes[..], 233u32.serialize::<DownwardBytes>().unwrap()[..]);
+        assert_eq!(Box::<u32>::deserialize(&bytes[..]).unwrap(), ser);
+        assert_eq!(*Rc::<u32>::deserialize(&bytes[..]).unwrap(), 233);
+        assert_eq!(*Arc::<u32>::deserialize(&bytes[..]).unwrap(), 233);
+
+        let ser: Arc<str> = Arc::from("hello");
+        let bytes = ser.serialize::<DownwardBytes>().unwrap();
+        assert_eq!(bytes[..], "hello".serialize::<DownwardBytes>().unwrap()[..]);
+        assert_eq!(Arc::<str>::deserialize(&bytes[..]).unwrap(), ser);
+        assert_eq!(&*Box::<str>::deserialize(&bytes[..]).unwrap(), "hello");
+        assert_eq!(&*Rc::<str>::deserialize(&bytes[..]).unwrap(), "hello");
+
+        let ser: Box<[String]> = vec!["a".to_owned(), "b".to_owned()].into();
+        let bytes = ser.serialize::<DownwardBytes>().unwrap();
+        assert_eq!(
+            bytes[..],
+            ser.to_vec().serialize::<DownwardBytes>().unwrap()[..]
+        );
+        assert_eq!(Box::<[String]>::deserialize(&bytes[..]).unwrap(), ser);
+        assert_eq!(&*Arc::<[String]>::deserialize(&bytes[..]).unwrap(), &*ser);
+        assert_eq!(&*Rc::<[String]>::deserialize(&bytes[..]).unwrap(), &*ser);
+
+        let ser: Box<[u8]> = Box::from(&b"bytes"[..]);
+        let bytes = ser.serialize::<DownwardBytes>().unwrap();
+        assert_eq!(
+            bytes[..],
+            b"bytes"[..].serialize::<DownwardBytes>().unwrap()[..]
+        );
+        assert_eq!(Box::<[u8]>::deserialize(&bytes[..]).unwrap(), ser);
+    }
+}
@@ -6,6 +6,48 @@ macro_rules! primitive_impl {
             fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
                 serializer.prepend(&self.to_le_bytes())
             }
+
+            fn serialize_slice_to<S: Serializer>(items: &[Self], serializer: &mut S) -> Result<()> {
+                #[cfg(target_endian = "little")]
+                {
+                    // SAFETY: the items are integers or floats without padding, so all
+                    // `size_of_val(items)` bytes are initialized, and they are already in
+                    // little-endian order on this host.
+                    let bytes = unsafe {
+                        std::slice::from_raw_parts(
+                            items.as_ptr() as *const u8,
+                            std::mem::size_of_val(items),
+                        )
+                    };
+                    serializer.prepend(bytes)
+                }
+                #[cfg(not(target_endian = "little"))]
+                {
+                    serializer.prepend(le_bytes(items, |v| v.to_le_bytes()))
+                }
+            }
+
+            fn serialize_slice_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+                items: &'a [Self],
+                serializer: &mut S,
+            ) -> Result<()> {
+                #[cfg(target_endian = "little")]
+                {
+                    // SAFETY: as in `serialize_slice_to`, and the bytes are borrowed from
+                    // `items` for `'a`.
+                    let bytes = unsafe {
+                        std::slice::from_raw_parts(
+                            items.as_ptr() as *const u8,
+                            std::mem::size_of_val(items),
+                        )
+                    };
+                    serializer.prepend_borrowed(bytes)
+                }
+                #[cfg(not(target_endian = "little"))]
+                {
+                    Self::serialize_slice_to(items, serializer)
+                }
+            }
         }
 
         impl<'a> Deserialize<'a> for $t {
@@ -16,12 +58,33 @@ macro_rules! primitive_impl {
                 let front = buf.pop(std::mem::size_of::<Self>())?;
                 Ok(Self::from_le_bytes(front.as_ref().try_into().unwrap()))
             }
+
+            fn deserialize_vec_from<D: Deserializer<'a>>(buf: &mut D, len: usize) -> Result<Vec<Self>>
+            where
+                Self: Sized,
+            {
+                let size = std::mem::size_of::<Self>();
+                let front = buf.pop(len.saturating_mul(size))?;
+                Ok(front
+                    .chunks_exact(size)
+                    .map(|chunk| Self::from_le_bytes(chunk.try_into().unwrap()))
+                    .collect())
+            }
         })*
     };
 }
 
 primitive_impl! {i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64}
 
+/// Collects the little-endian bytes of the items, for big-endian hosts.
+///
+/// CI only runs on little-endian hosts, where the branch calling this is not compiled, so
+/// the function is covered by the unit tests instead.
+#[cfg_attr(target_endian = "little", allow(dead_code))]
+fn le_bytes<T, const N: usize>(items: &[T], f: impl Fn(&T) -> [u8; N]) -> Vec<u8> {
+    items.iter().flat_map(f).collect()
+}
+
 impl Serialize for bool {
     fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
         serializer.prepend([*self as u8])
@@ -92,12 +155,26 @@ impl<T: Serialize + ?Sized> Serialize for &T {
     fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
         T::serialize_to(self, serializer)
     }
+
+    fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+        &'a self,
+        serializer: &mut S,
+    ) -> Result<()> {
+        T::serialize_borrowed_to(self, serializer)
+    }
 }
 
 impl<T: Serialize + ?Sized> Serialize for &mut T {
     fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
         T::serialize_to(self, serializer)
     }
+
+    fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+        &'a self,
+        serializer: &mut S,
+    ) -> Result<()> {
+        T::serialize_borrowed_to(self, serializer)
+    }
 }
 
 impl Serialize for () {
@@ -124,6 +201,18 @@ impl<Item: Serialize> Serialize for Option<Item> {
             false.serialize_to(serializer)
         }
     }
+
+    fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+        &'a self,
+        serializer: &mut S,
+    ) -> Result<()> {
+        if let Some(item) = self {
+            item.serialize_borrowed_to(serializer)?;
+            true.serialize_to(serializer)
+        } else {
+            false.serialize_to(serializer)
+        }
+    }
 }
 
 impl<'a, Item: Deserialize<'a>> Deserialize<'a> for Option<Item> {
@@ -144,6 +233,23 @@ impl<'a, Item: Deserialize<'a>> Deserialize<'a> for Option<Item> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_le_bytes() {
+        let items = [1u32, 0x01020304, u32::MAX];
+        let bytes = le_bytes(&items, |v| v.to_le_bytes());
+        assert_eq!(bytes, [1, 0, 0, 0, 4, 3, 2, 1, 255, 255, 255, 255]);
+
+        let mut serializer = DownwardBytes::new();
+        u32::serialize_slice_to(&items, &mut serializer).unwrap();
+        assert_eq!(serializer.as_slice(), bytes);
+
+        let items = [-1.5f64, 0.25];
+        let bytes = le_bytes(&items, |v| v.to_le_bytes());
+        assert_eq!(bytes.len(), 16);
+        let mut buf = &bytes[..];
+        assert_eq!(f64::deserialize_vec_from(&mut buf, 2).unwrap(), items);
+    }
+
     #[test]
     fn test_primitive_types() {
         for ser in [u64::MIN, u64::MAX] {
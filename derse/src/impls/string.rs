@@ -6,6 +6,14 @@ impl Serialize for str {
         serializer.prepend(self.as_bytes())?;
         VarInt64(self.len() as u64).serialize_to(serializer)
     }
+
+    fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+        &'a self,
+        serializer: &mut S,
+    ) -> Result<()> {
+        serializer.prepend_borrowed(self.as_bytes())?;
+        VarInt64(self.len() as u64).serialize_to(serializer)
+    }
 }
 
 impl<'a> Deserialize<'a> for &'a str {
@@ -14,6 +22,7 @@ impl<'a> Deserialize<'a> for &'a str {
         Self: Sized,
     {
         let len = VarInt64::deserialize_from(buf)?.0 as usize;
+        buf.check_bytes_len(len)?;
         let front = buf.pop(len)?;
         match front {
             Cow::Borrowed(borrowed) => match std::str::from_utf8(borrowed) {
@@ -30,6 +39,14 @@ impl Serialize for [u8] {
         serializer.prepend(self)?;
         VarInt64(self.len() as u64).serialize_to(serializer)
     }
+
+    fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+        &'a self,
+        serializer: &mut S,
+    ) -> Result<()> {
+        serializer.prepend_borrowed(self)?;
+        VarInt64(self.len() as u64).serialize_to(serializer)
+    }
 }
 
 impl<'a> Deserialize<'a> for &'a [u8] {
@@ -38,6 +55,7 @@ impl<'a> Deserialize<'a> for &'a [u8] {
         Self: Sized,
     {
         let len = VarInt64::deserialize_from(buf)?.0 as usize;
+        buf.check_bytes_len(len)?;
         let front = buf.pop(len)?;
         match front {
             Cow::Borrowed(borrowed) => Ok(borrowed),
@@ -50,6 +68,13 @@ impl Serialize for String {
     fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
         self.as_str().serialize_to(serializer)
     }
+
+    fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+        &'a self,
+        serializer: &mut S,
+    ) -> Result<()> {
+        self.as_str().serialize_borrowed_to(serializer)
+    }
 }
 
 impl<'a> Deserialize<'a> for String {
@@ -122,8 +147,8 @@ mod tests {
 
         {
             let msg = "0".repeat(47) + "A";
-            let a = msg[..25].as_bytes();
-            let b = msg[24..].as_bytes();
+            let a = &msg.as_bytes()[..25];
+            let b = &msg.as_bytes()[24..];
             let c = [a, b];
 
             let der = String::deserialize(BytesArray::new(&c)).unwrap();
@@ -5,9 +5,7 @@ where
     A::Item: Serialize,
 {
     fn serialize_to<S: crate::Serializer>(&self, serializer: &mut S) -> crate::Result<()> {
-        for item in self.iter().rev() {
-            item.serialize_to(serializer)?;
-        }
+        A::Item::serialize_slice_to(self, serializer)?;
         VarInt64(self.len() as u64).serialize_to(serializer)
     }
 }
@@ -21,11 +19,18 @@ where
         Self: Sized,
     {
         let len = VarInt64::deserialize_from(buf)?.0 as usize;
-        let mut out = Self::with_capacity(len);
-        for _ in 0..len {
-            out.push(Deserialize::deserialize_from(buf)?);
-        }
-        Ok(out)
+        buf.check_collection_len(len, std::mem::size_of::<A::Item>())?;
+        buf.enter_nested()?;
+        // The length is untrusted, so the vector grows as items are read instead.
+        let mut out = Self::default();
+        let result = (0..len).try_for_each(|i| {
+            out.push(
+                Deserialize::deserialize_from(buf).map_err(|e| e.with_index(i, buf.remaining()))?,
+            );
+            Ok(())
+        });
+        buf.leave_nested();
+        result.map(|()| out)
     }
 }
 
@@ -40,5 +45,14 @@ mod tests {
         let bytes = ser.serialize::<DownwardBytes>().unwrap();
         let der = Bytes::deserialize(&bytes[..]).unwrap();
         assert_eq!(ser, der);
+
+        let bytes = VarInt64(u64::MAX).serialize::<DownwardBytes>().unwrap();
+        assert_eq!(
+            Bytes::deserialize(&bytes[..]).unwrap_err().root_cause(),
+            &Error::DataIsShort {
+                expect: 1,
+                actual: 0
+            }
+        );
     }
 }
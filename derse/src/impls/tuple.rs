@@ -10,6 +10,14 @@ macro_rules! tuple_serialization {
                 $((self.$idx.serialize_to(serializer))?;)+
                 Ok(())
             }
+
+            fn serialize_borrowed_to<'b, S: BorrowingSerializer<'b>>(
+                &'b self,
+                serializer: &mut S,
+            ) -> Result<()> {
+                $((self.$idx.serialize_borrowed_to(serializer))?;)+
+                Ok(())
+            }
         }
 
         impl<'a, $($name),+> Deserialize<'a> for ($($name,)+)
@@ -1,17 +1,35 @@
+mod array_bytes;
 mod bytes_array;
+#[cfg(feature = "tokio-util")]
+mod codec;
 mod deserializer;
 mod downward_bytes;
 mod error;
 mod impls;
+mod limits;
+mod reader_deserializer;
 mod serializer;
+mod shared;
+mod slice_serializer;
+pub mod varint;
 mod varint64;
+mod vectored_bytes;
 
+pub use array_bytes::ArrayBytes;
 pub use bytes_array::BytesArray;
+#[cfg(feature = "tokio-util")]
+pub use codec::DerseCodec;
 pub use deserializer::Deserializer;
 pub use downward_bytes::DownwardBytes;
-pub use error::{Error, Result};
-pub use serializer::Serializer;
+pub use error::{Error, ErrorContext, Result};
+pub use limits::{LimitedDeserializer, Limits};
+pub use reader_deserializer::ReaderDeserializer;
+pub use serializer::{BorrowingSerializer, Serializer};
+pub use shared::{Shared, SharedDeserializer, SharedPointers, SharedSerializer, SharedVisit};
+pub use slice_serializer::SliceSerializer;
+pub use varint::{VarI16, VarI32, VarI64, VarIntValue, VarIsize, VarU16, VarU32, VarU64, VarUsize};
 pub use varint64::VarInt64;
+pub use vectored_bytes::VectoredBytes;
 
 pub use derse_derive::{Deserialize, Serialize};
 
@@ -38,6 +56,73 @@ pub trait Serialize {
     ///
     /// A `Result` indicating success or failure.
     fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()>;
+
+    /// Serializes the items of a slice in order, without a length prefix.
+    ///
+    /// Collections of contiguous items call this instead of serializing each item, so
+    /// that fixed-size primitives can prepend the whole slice at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The items to serialize.
+    /// * `serializer` - The `Serializer` to serialize the data into.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn serialize_slice_to<S: Serializer>(items: &[Self], serializer: &mut S) -> Result<()>
+    where
+        Self: Sized,
+    {
+        for item in items.iter().rev() {
+            item.serialize_to(serializer)?;
+        }
+        Ok(())
+    }
+
+    /// Serializes the data into a `BorrowingSerializer`, which may reference the byte
+    /// sequences of the value instead of copying them.
+    ///
+    /// The output is the same as `serialize_to`. The default implementation calls
+    /// `serialize_to`, and derived types, byte sequences and containers override it.
+    ///
+    /// # Arguments
+    ///
+    /// * `serializer` - The `BorrowingSerializer` to serialize the data into.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn serialize_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+        &'a self,
+        serializer: &mut S,
+    ) -> Result<()> {
+        self.serialize_to(serializer)
+    }
+
+    /// Serializes the items of a slice like `serialize_slice_to`, into a
+    /// `BorrowingSerializer`.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The items to serialize.
+    /// * `serializer` - The `BorrowingSerializer` to serialize the data into.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    fn serialize_slice_borrowed_to<'a, S: BorrowingSerializer<'a>>(
+        items: &'a [Self],
+        serializer: &mut S,
+    ) -> Result<()>
+    where
+        Self: Sized,
+    {
+        for item in items.iter().rev() {
+            item.serialize_borrowed_to(serializer)?;
+        }
+        Ok(())
+    }
 }
 
 /// A trait for deserializing data.
@@ -50,12 +135,14 @@ pub trait Deserialize<'a> {
     ///
     /// # Returns
     ///
-    /// A `Result` containing the deserialized data or an error.
+    /// A `Result` containing the deserialized data or an error. The offset of an
+    /// `Error::Context` is counted from the start of `der`.
     fn deserialize<D: Deserializer<'a>>(mut der: D) -> Result<Self>
     where
         Self: Sized,
     {
-        Self::deserialize_from(&mut der)
+        let start = der.remaining();
+        Self::deserialize_from(&mut der).map_err(|e| e.with_start(start))
     }
 
     /// Deserializes the data from the given `Deserializer`.
@@ -70,6 +157,27 @@ pub trait Deserialize<'a> {
     fn deserialize_from<D: Deserializer<'a>>(buf: &mut D) -> Result<Self>
     where
         Self: Sized;
+
+    /// Deserializes `len` items written by `serialize_slice_to` into a `Vec`.
+    ///
+    /// Fixed-size primitives override this to pop all items at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - The `Deserializer` to deserialize the data from.
+    /// * `len` - The number of items.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the deserialized items or an error.
+    fn deserialize_vec_from<D: Deserializer<'a>>(buf: &mut D, len: usize) -> Result<Vec<Self>>
+    where
+        Self: Sized,
+    {
+        (0..len)
+            .map(|i| Self::deserialize_from(buf).map_err(|e| e.with_index(i, buf.remaining())))
+            .collect()
+    }
 }
 
 /// A trait for detailed deserialization.
@@ -98,3 +206,55 @@ pub trait DetailedDeserialize<'a> {
     where
         Self: Sized;
 }
+
+/// Serializes the value and writes it to the given writer.
+///
+/// The serialized size is measured first, so the value is built in a single buffer of the
+/// exact size and written with one `write_all`, without growing or copying the buffer.
+///
+/// # Arguments
+///
+/// * `value` - The value to serialize.
+/// * `writer` - The writer to write the serialized data to.
+///
+/// # Returns
+///
+/// A `Result` indicating success or failure.
+pub fn to_writer<T: Serialize + ?Sized, W: std::io::Write>(
+    value: &T,
+    writer: &mut W,
+) -> Result<()> {
+    let len = value.serialize::<usize>()?;
+    let mut bytes = DownwardBytes::with_capacity(len);
+    value.serialize_to(&mut bytes)?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_writer() {
+        let ser = ("hello".to_owned(), vec![1u64, 2, 3], Some(4.5f64));
+        let mut out = Vec::new();
+        to_writer(&ser, &mut out).unwrap();
+        to_writer("world", &mut out).unwrap();
+        assert_eq!(
+            &out[..ser.serialize::<usize>().unwrap()],
+            &ser.serialize::<DownwardBytes>().unwrap()[..]
+        );
+
+        let mut der = ReaderDeserializer::new(&out[..]);
+        let value = <(String, Vec<u64>, Option<f64>)>::deserialize_from(&mut der).unwrap();
+        assert_eq!(value, ser);
+        assert_eq!(String::deserialize_from(&mut der).unwrap(), "world");
+
+        let mut full = [0u8; 4];
+        assert!(matches!(
+            to_writer(&ser, &mut &mut full[..]),
+            Err(Error::IoError(_))
+        ));
+    }
+}
@@ -0,0 +1,315 @@
+use super::{Deserializer, Error, Result, SharedPointers};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Limits enforced by a `LimitedDeserializer` on untrusted input.
+///
+/// The default value sets no limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum number of items in a collection.
+    pub max_collection_len: usize,
+    /// The maximum length of a byte sequence, such as a string.
+    pub max_bytes_len: usize,
+    /// The maximum number of bytes allocated for collections and byte sequences in total.
+    pub max_total_bytes: usize,
+    /// The maximum nesting depth of derived types and collections.
+    pub max_depth: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_collection_len: usize::MAX,
+            max_bytes_len: usize::MAX,
+            max_total_bytes: usize::MAX,
+            max_depth: usize::MAX,
+        }
+    }
+}
+
+/// A deserializer wrapper rejecting lengths and nesting depths above the given `Limits`.
+///
+/// Collection and string impls report their decoded lengths before allocating, and
+/// `Error::LimitExceeded` is returned as soon as one of the limits is crossed. Deserializers
+/// returned by `advance` share the total byte budget with the one they came from, and start
+/// at its current depth.
+pub struct LimitedDeserializer<D> {
+    inner: D,
+    limits: Limits,
+    total_bytes: Rc<Cell<usize>>,
+    depth: usize,
+}
+
+impl<D> LimitedDeserializer<D> {
+    /// Creates a new `LimitedDeserializer` wrapping the given deserializer.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The deserializer to read the data from.
+    /// * `limits` - The limits to enforce.
+    pub fn new(inner: D, limits: Limits) -> Self {
+        Self {
+            inner,
+            limits,
+            total_bytes: Rc::default(),
+            depth: 0,
+        }
+    }
+
+    /// Returns the enforced limits.
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Returns the number of bytes accounted against `max_total_bytes` so far.
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes.get()
+    }
+
+    /// Returns the current nesting depth.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the wrapped deserializer.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+
+    /// Accounts the given number of bytes against the total byte limit.
+    fn account(&self, bytes: usize) -> Result<()> {
+        let total = self.total_bytes().saturating_add(bytes);
+        if total > self.limits.max_total_bytes {
+            return Err(Error::LimitExceeded(format!(
+                "total bytes {} exceed the limit {}",
+                total, self.limits.max_total_bytes
+            )));
+        }
+        self.total_bytes.set(total);
+        Ok(())
+    }
+}
+
+impl<'a, D: Deserializer<'a>> Deserializer<'a> for LimitedDeserializer<D> {
+    /// Checks if the wrapped deserializer is empty.
+    fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns the number of bytes left in the wrapped deserializer.
+    fn remaining(&self) -> usize {
+        self.inner.remaining()
+    }
+
+    /// Advances the wrapped deserializer by the specified length.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The length to advance.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a deserializer with the same limits or an error.
+    fn advance(&mut self, len: usize) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            inner: self.inner.advance(len)?,
+            limits: self.limits,
+            total_bytes: self.total_bytes.clone(),
+            depth: self.depth,
+        })
+    }
+
+    /// Pops the specified length of data from the wrapped deserializer.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The length of data to pop.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the popped data or an error.
+    fn pop(&mut self, len: usize) -> Result<Cow<'a, [u8]>> {
+        self.inner.pop(len)
+    }
+
+    /// Pops the specified length of data from the wrapped deserializer as `bytes::Bytes`.
+    #[cfg(feature = "bytes")]
+    fn pop_bytes(&mut self, len: usize) -> Result<bytes::Bytes> {
+        self.inner.pop_bytes(len)
+    }
+
+    /// Checks the collection length against `max_collection_len` and `max_total_bytes`.
+    fn check_collection_len(&mut self, len: usize, item_size: usize) -> Result<()> {
+        if len > self.limits.max_collection_len {
+            return Err(Error::LimitExceeded(format!(
+                "collection length {} exceeds the limit {}",
+                len, self.limits.max_collection_len
+            )));
+        }
+        self.account(len.saturating_mul(item_size))?;
+        self.inner.check_collection_len(len, item_size)
+    }
+
+    /// Checks the byte length against `max_bytes_len` and `max_total_bytes`.
+    fn check_bytes_len(&mut self, len: usize) -> Result<()> {
+        if len > self.limits.max_bytes_len {
+            return Err(Error::LimitExceeded(format!(
+                "byte length {} exceeds the limit {}",
+                len, self.limits.max_bytes_len
+            )));
+        }
+        self.account(len)?;
+        self.inner.check_bytes_len(len)
+    }
+
+    /// Increases the nesting depth, checking it against `max_depth`.
+    fn enter_nested(&mut self) -> Result<()> {
+        if self.depth >= self.limits.max_depth {
+            return Err(Error::LimitExceeded(format!(
+                "depth exceeds the limit {}",
+                self.limits.max_depth
+            )));
+        }
+        self.depth += 1;
+        self.inner.enter_nested()
+    }
+
+    /// Decreases the nesting depth.
+    fn leave_nested(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        self.inner.leave_nested();
+    }
+
+    /// Returns the shared pointers of the wrapped deserializer.
+    fn shared_pointers(&self) -> Option<&SharedPointers> {
+        self.inner.shared_pointers()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_limits() {
+        let ser = vec!["hello".to_owned(); 10];
+        let bytes = ser.serialize::<DownwardBytes>().unwrap();
+
+        let der =
+            Vec::<String>::deserialize(LimitedDeserializer::new(&bytes[..], Limits::default()))
+                .unwrap();
+        assert_eq!(der, ser);
+
+        let limits = Limits {
+            max_collection_len: 9,
+            ..Default::default()
+        };
+        assert_eq!(
+            Vec::<String>::deserialize(LimitedDeserializer::new(&bytes[..], limits)),
+            Err(Error::LimitExceeded(
+                "collection length 10 exceeds the limit 9".to_owned()
+            ))
+        );
+
+        let limits = Limits {
+            max_bytes_len: 4,
+            ..Default::default()
+        };
+        assert_eq!(
+            Vec::<String>::deserialize(LimitedDeserializer::new(&bytes[..], limits))
+                .unwrap_err()
+                .root_cause(),
+            &Error::LimitExceeded("byte length 5 exceeds the limit 4".to_owned())
+        );
+
+        let size = 10 * std::mem::size_of::<String>() + 10 * 5;
+        let mut der = LimitedDeserializer::new(
+            &bytes[..],
+            Limits {
+                max_total_bytes: size,
+                ..Default::default()
+            },
+        );
+        assert_eq!(Vec::<String>::deserialize_from(&mut der).unwrap(), ser);
+        assert_eq!(der.total_bytes(), size);
+
+        let limits = Limits {
+            max_total_bytes: size - 1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            Vec::<String>::deserialize(LimitedDeserializer::new(&bytes[..], limits))
+                .unwrap_err()
+                .root_cause(),
+            Error::LimitExceeded(_)
+        ));
+    }
+
+    #[test]
+    fn test_depth() {
+        let ser = vec![vec![vec![1u8]]];
+        let bytes = ser.serialize::<DownwardBytes>().unwrap();
+
+        let limits = Limits {
+            max_depth: 3,
+            ..Default::default()
+        };
+        let mut der = LimitedDeserializer::new(&bytes[..], limits);
+        assert_eq!(
+            Vec::<Vec<Vec<u8>>>::deserialize_from(&mut der).unwrap(),
+            ser
+        );
+        assert_eq!(der.depth(), 0);
+
+        let limits = Limits {
+            max_depth: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            Vec::<Vec<Vec<u8>>>::deserialize(LimitedDeserializer::new(&bytes[..], limits))
+                .unwrap_err()
+                .root_cause(),
+            &Error::LimitExceeded("depth exceeds the limit 2".to_owned())
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_allocation_bomb() {
+        let bytes = VarInt64(u64::MAX).serialize::<DownwardBytes>().unwrap();
+        let limits = Limits {
+            max_collection_len: 1 << 20,
+            max_bytes_len: 1 << 20,
+            max_total_bytes: 1 << 24,
+            max_depth: 64,
+        };
+
+        assert!(matches!(
+            Vec::<()>::deserialize(LimitedDeserializer::new(&bytes[..], limits)),
+            Err(Error::LimitExceeded(_))
+        ));
+        assert!(matches!(
+            HashMap::<u8, u8>::deserialize(LimitedDeserializer::new(&bytes[..], limits)),
+            Err(Error::LimitExceeded(_))
+        ));
+        assert!(matches!(
+            String::deserialize(LimitedDeserializer::new(&bytes[..], limits)),
+            Err(Error::LimitExceeded(_))
+        ));
+
+        let ser = vec![vec![0u64; 1 << 19]; 4];
+        let bytes = ser.serialize::<DownwardBytes>().unwrap();
+        assert!(matches!(
+            Vec::<Vec<u64>>::deserialize(LimitedDeserializer::new(&bytes[..], limits))
+                .unwrap_err()
+                .root_cause(),
+            Error::LimitExceeded(_)
+        ));
+    }
+}
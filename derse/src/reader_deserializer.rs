@@ -0,0 +1,248 @@
+use super::{Deserializer, Error, Result};
+use std::borrow::Cow;
+use std::io::Read;
+use std::sync::Arc;
+
+/// A deserializer reading data from a `std::io::Read` source.
+///
+/// Popped data is always owned, so it implements `Deserializer<'static>` and can decode
+/// types that do not borrow from the input, such as `String`, `Vec<T>` and derived structs.
+///
+/// `advance` reads the requested length from the reader into a buffer, and the returned
+/// `ReaderDeserializer` works on that buffer. Reads are issued for every `pop`, so wrap
+/// unbuffered sources such as files and sockets in a `std::io::BufReader`.
+pub struct ReaderDeserializer<R> {
+    source: Source<R>,
+    read: usize,
+}
+
+/// Where a `ReaderDeserializer` takes its data from.
+enum Source<R> {
+    /// Data is read from the reader on demand.
+    Reader(R),
+    /// Data is taken from the `pos..end` range of a buffer read by `advance`.
+    Buffer {
+        data: Arc<Vec<u8>>,
+        pos: usize,
+        end: usize,
+    },
+}
+
+impl<R: Read> ReaderDeserializer<R> {
+    /// Creates a new `ReaderDeserializer` reading from the given reader.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The reader to deserialize the data from.
+    pub fn new(reader: R) -> Self {
+        Self {
+            source: Source::Reader(reader),
+            read: 0,
+        }
+    }
+
+    /// Returns the underlying reader, or `None` if this deserializer was returned by `advance`.
+    pub fn into_inner(self) -> Option<R> {
+        match self.source {
+            Source::Reader(reader) => Some(reader),
+            Source::Buffer { .. } => None,
+        }
+    }
+
+    /// Takes the next `len` bytes from the source.
+    fn take(&mut self, len: usize) -> Result<Vec<u8>> {
+        match &mut self.source {
+            Source::Reader(reader) => {
+                let vec = read_exact(reader, len)?;
+                self.read += len;
+                Ok(vec)
+            }
+            Source::Buffer { data, pos, end } => {
+                if len <= *end - *pos {
+                    let vec = data[*pos..*pos + len].to_vec();
+                    *pos += len;
+                    Ok(vec)
+                } else {
+                    Err(Error::DataIsShort {
+                        expect: len,
+                        actual: *end - *pos,
+                    })
+                }
+            }
+        }
+    }
+}
+
+/// Reads exactly `len` bytes from the reader.
+///
+/// The buffer grows as data arrives, so a corrupted length does not allocate up front.
+fn read_exact<R: Read>(reader: &mut R, len: usize) -> Result<Vec<u8>> {
+    let mut vec = Vec::new();
+    reader.take(len as u64).read_to_end(&mut vec)?;
+    if vec.len() < len {
+        return Err(Error::DataIsShort {
+            expect: len,
+            actual: vec.len(),
+        });
+    }
+    Ok(vec)
+}
+
+impl<R: Read> Deserializer<'static> for ReaderDeserializer<R> {
+    /// Checks if the deserializer is empty.
+    ///
+    /// A deserializer reading directly from the reader cannot tell whether more data
+    /// follows without consuming it, so it always returns `false`.
+    fn is_empty(&self) -> bool {
+        match &self.source {
+            Source::Reader(_) => false,
+            Source::Buffer { pos, end, .. } => pos == end,
+        }
+    }
+
+    /// Returns the number of bytes left.
+    ///
+    /// The length of a reader is unknown, so a deserializer reading directly from the
+    /// reader returns `usize::MAX` minus the number of bytes read so far.
+    fn remaining(&self) -> usize {
+        match &self.source {
+            Source::Reader(_) => usize::MAX - self.read,
+            Source::Buffer { pos, end, .. } => end - pos,
+        }
+    }
+
+    /// Advances the deserializer by the specified length.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The length to advance.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a deserializer over the buffered data or an error.
+    fn advance(&mut self, len: usize) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        let source = match &mut self.source {
+            Source::Reader(reader) => {
+                let data = read_exact(reader, len)?;
+                self.read += len;
+                Source::Buffer {
+                    data: Arc::new(data),
+                    pos: 0,
+                    end: len,
+                }
+            }
+            Source::Buffer { data, pos, end } => {
+                if len > *end - *pos {
+                    return Err(Error::DataIsShort {
+                        expect: len,
+                        actual: *end - *pos,
+                    });
+                }
+                let start = *pos;
+                *pos += len;
+                Source::Buffer {
+                    data: data.clone(),
+                    pos: start,
+                    end: start + len,
+                }
+            }
+        };
+        Ok(Self { source, read: 0 })
+    }
+
+    /// Pops the specified length of data from the deserializer.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The length of data to pop.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the popped data or an error.
+    fn pop(&mut self, len: usize) -> Result<Cow<'static, [u8]>> {
+        self.take(len).map(Cow::Owned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_reader_deserializer() {
+        let ser = (
+            "hello".to_owned(),
+            vec![1u32, 2, 3],
+            HashMap::from([(1u8, "one".to_owned())]),
+        );
+        let bytes = ser.serialize::<DownwardBytes>().unwrap();
+
+        let mut der = ReaderDeserializer::new(&bytes[..]);
+        assert!(!der.is_empty());
+        let value = <(String, Vec<u32>, HashMap<u8, String>)>::deserialize_from(&mut der).unwrap();
+        assert_eq!(value, ser);
+        assert!(der.into_inner().unwrap().is_empty());
+
+        let mut der = ReaderDeserializer::new(&bytes[..bytes.len() - 1]);
+        assert!(matches!(
+            <(String, Vec<u32>, HashMap<u8, String>)>::deserialize_from(&mut der)
+                .unwrap_err()
+                .root_cause(),
+            Error::DataIsShort { .. }
+        ));
+    }
+
+    #[test]
+    fn test_advance() {
+        let data = (0u8..10).collect::<Vec<_>>();
+        let mut der = ReaderDeserializer::new(&data[..]);
+
+        let front = der.advance(6).unwrap();
+        assert_eq!(der.pop(2).unwrap(), &data[6..8]);
+        assert!(front.into_inner().is_none());
+
+        let mut front = der.advance(2).unwrap();
+        assert!(front.advance(3).is_err());
+        assert_eq!(front.pop(1).unwrap(), &data[8..9]);
+        assert!(!front.is_empty());
+        assert_eq!(front.pop(1).unwrap(), &data[9..]);
+        assert!(front.is_empty());
+        assert!(front.pop(1).is_err());
+
+        assert_eq!(
+            der.advance(1).err(),
+            Some(Error::DataIsShort {
+                expect: 1,
+                actual: 0
+            })
+        );
+
+        let mut der = ReaderDeserializer::new(&data[..]);
+        let mut front = der.advance(10).unwrap();
+        let mut inner = front.advance(4).unwrap();
+        assert_eq!(front.pop(6).unwrap(), &data[4..]);
+        assert_eq!(inner.pop(4).unwrap(), &data[..4]);
+        assert!(inner.is_empty() && front.is_empty());
+    }
+
+    #[test]
+    fn test_io_error() {
+        struct Broken;
+
+        impl std::io::Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("broken pipe"))
+            }
+        }
+
+        let mut der = ReaderDeserializer::new(Broken);
+        assert_eq!(
+            String::deserialize_from(&mut der),
+            Err(Error::IoError("broken pipe".to_owned()))
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use super::Result;
+use super::{Result, SharedVisit};
 
 /// A trait for serializing data into a byte buffer.
 pub trait Serializer {
@@ -13,6 +13,19 @@ pub trait Serializer {
     /// Returns an error if the operation fails.
     fn prepend(&mut self, data: impl AsRef<[u8]>) -> Result<()>;
 
+    /// Prepends a `bytes::Bytes` buffer.
+    ///
+    /// Serializers producing chunks can keep a reference to the buffer instead of copying it.
+    /// The default implementation calls `prepend`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operation fails.
+    #[cfg(feature = "bytes")]
+    fn prepend_bytes(&mut self, data: &bytes::Bytes) -> Result<()> {
+        self.prepend(data)
+    }
+
     /// Returns the length of the serialized data.
     fn len(&self) -> usize;
 
@@ -20,6 +33,32 @@ pub trait Serializer {
     fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Visits a `Shared` pointer pointing at the given address.
+    ///
+    /// Returns how the pointer is written, or `None` if the serializer does not track shared
+    /// pointers, in which case its value is written as is. The default implementation
+    /// returns `None`.
+    fn visit_shared(&mut self, _addr: usize) -> Option<SharedVisit> {
+        None
+    }
+}
+
+/// A serializer that can reference data borrowed for `'a` instead of copying it.
+///
+/// `Serialize::serialize_borrowed_to` passes the byte sequences of a value borrowed for `'a`
+/// to `prepend_borrowed`, such as the contents of `Vec<u8>`, `String` and slices.
+pub trait BorrowingSerializer<'a>: Serializer {
+    /// Prepends data borrowed for `'a`.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data to prepend.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operation fails.
+    fn prepend_borrowed(&mut self, data: &'a [u8]) -> Result<()>;
 }
 
 impl Serializer for usize {
@@ -0,0 +1,450 @@
+use super::{Deserialize, Deserializer, Error, Result, Serialize, Serializer, VarInt64};
+use std::any::Any;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A `Rc` or `Arc` written once when shared within a value serialized by a `SharedSerializer`.
+///
+/// Plain `Rc` and `Arc` are always written with their value. Wrapping them in `Shared` opts
+/// into deduplication, which requires the pointee to be `'static` when deserializing, since
+/// a `SharedDeserializer` keeps the decoded pointers as `dyn Any`. Other serializers and
+/// deserializers write and read a `Shared` exactly like the pointer it wraps.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Shared<P>(pub P);
+
+impl<P> From<P> for Shared<P> {
+    fn from(pointer: P) -> Self {
+        Self(pointer)
+    }
+}
+
+impl<P> std::ops::Deref for Shared<P> {
+    type Target = P;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// How a `Shared` pointer is written by a serializer tracking shared pointers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SharedVisit {
+    /// The pointer is not shared, and its value is written with the id 0.
+    Inline,
+    /// This is the first occurrence of a shared pointer, written with its value.
+    Full(u64),
+    /// The value of the shared pointer was already written, so only its id is written.
+    Ref(u64),
+}
+
+/// The number of times a pointer is visited and the id assigned to it if it is shared.
+struct SharedEntry {
+    visits: usize,
+    id: Option<u64>,
+}
+
+/// A serializer wrapper writing every `Shared` pointer shared within a value only once.
+///
+/// Data is prepended, so the first occurrence of a pointer in the output is the last one
+/// serialized. `serialize_value` therefore walks the value twice: once to count the
+/// occurrences of each pointer, and once to write its value at the first occurrence and a
+/// back-reference at the others.
+///
+/// Shared pointers are written as a `VarInt64` id followed by the value, where the id is 0 for
+/// pointers occurring once and the shared id plus one otherwise. Only a `SharedDeserializer`
+/// reads this format back.
+///
+/// Pointers are identified by their address, so serializing a value must not create and drop
+/// `Shared` pointers, as a later one could reuse the address.
+pub struct SharedSerializer<S> {
+    inner: S,
+    pointers: HashMap<usize, SharedEntry>,
+    next_id: u64,
+    counting: bool,
+}
+
+impl<S: Serializer> SharedSerializer<S> {
+    /// Creates a new `SharedSerializer` wrapping the given serializer.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The serializer to write the data to.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            pointers: HashMap::new(),
+            next_id: 0,
+            counting: false,
+        }
+    }
+
+    /// Serializes the value, writing the value of each shared pointer once.
+    ///
+    /// Values serialized by calling `serialize_to` directly are written without
+    /// deduplication, since their pointers were not counted.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to serialize.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let mut counter = SharedSerializer {
+            inner: 0usize,
+            pointers: HashMap::new(),
+            next_id: self.next_id,
+            counting: true,
+        };
+        value.serialize_to(&mut counter)?;
+        self.pointers = counter.pointers;
+        self.next_id = counter.next_id;
+        let result = value.serialize_to(self);
+        self.pointers.clear();
+        result
+    }
+
+    /// Returns the wrapped serializer.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: Serializer> Serializer for SharedSerializer<S> {
+    /// Prepends data to the wrapped serializer.
+    fn prepend(&mut self, data: impl AsRef<[u8]>) -> Result<()> {
+        self.inner.prepend(data)
+    }
+
+    /// Prepends a `bytes::Bytes` buffer to the wrapped serializer.
+    #[cfg(feature = "bytes")]
+    fn prepend_bytes(&mut self, data: &bytes::Bytes) -> Result<()> {
+        self.inner.prepend_bytes(data)
+    }
+
+    /// Returns the length of the serialized data.
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Counts or resolves an occurrence of the pointer at the given address.
+    fn visit_shared(&mut self, addr: usize) -> Option<SharedVisit> {
+        if self.counting {
+            let entry = self.pointers.entry(addr).or_insert(SharedEntry {
+                visits: 0,
+                id: None,
+            });
+            entry.visits += 1;
+            if entry.visits == 1 {
+                return Some(SharedVisit::Inline);
+            }
+            let id = *entry.id.get_or_insert_with(|| {
+                self.next_id += 1;
+                self.next_id - 1
+            });
+            return Some(SharedVisit::Ref(id));
+        }
+
+        Some(match self.pointers.get_mut(&addr) {
+            Some(SharedEntry {
+                visits,
+                id: Some(id),
+            }) => {
+                *visits -= 1;
+                if *visits == 0 {
+                    SharedVisit::Full(*id)
+                } else {
+                    SharedVisit::Ref(*id)
+                }
+            }
+            _ => SharedVisit::Inline,
+        })
+    }
+}
+
+/// The shared pointers decoded by a `SharedDeserializer`, indexed by id.
+///
+/// Clones refer to the same pointers, so deserializers returned by `advance` resolve
+/// back-references to values decoded by the one they came from.
+#[derive(Clone, Default)]
+pub struct SharedPointers(Rc<RefCell<HashMap<u64, Box<dyn Any>>>>);
+
+impl SharedPointers {
+    /// Returns the pointer with the given id, if it was decoded.
+    fn get<P: Clone + 'static>(&self, id: u64) -> Option<Result<P>> {
+        self.0.borrow().get(&id).map(|any| {
+            any.downcast_ref::<P>().cloned().ok_or_else(|| {
+                Error::InvalidType(format!("shared pointer {} has a different type", id))
+            })
+        })
+    }
+
+    /// Records the pointer with the given id.
+    fn insert<P: Clone + 'static>(&self, id: u64, pointer: P) {
+        self.0.borrow_mut().insert(id, Box::new(pointer));
+    }
+}
+
+/// A deserializer wrapper rebuilding the `Shared` pointers written by a `SharedSerializer`.
+///
+/// Back-references decode to clones of the pointer decoded at the first occurrence, so
+/// shared values are shared again after deserialization.
+pub struct SharedDeserializer<D> {
+    inner: D,
+    pointers: SharedPointers,
+}
+
+impl<D> SharedDeserializer<D> {
+    /// Creates a new `SharedDeserializer` wrapping the given deserializer.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The deserializer to read the data from.
+    pub fn new(inner: D) -> Self {
+        Self {
+            inner,
+            pointers: SharedPointers::default(),
+        }
+    }
+
+    /// Returns the wrapped deserializer.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+}
+
+impl<'a, D: Deserializer<'a>> Deserializer<'a> for SharedDeserializer<D> {
+    /// Checks if the wrapped deserializer is empty.
+    fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Advances the wrapped deserializer by the specified length.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The length to advance.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a deserializer sharing the decoded pointers or an error.
+    fn advance(&mut self, len: usize) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            inner: self.inner.advance(len)?,
+            pointers: self.pointers.clone(),
+        })
+    }
+
+    /// Pops the specified length of data from the wrapped deserializer.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The length of data to pop.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the popped data or an error.
+    fn pop(&mut self, len: usize) -> Result<Cow<'a, [u8]>> {
+        self.inner.pop(len)
+    }
+
+    /// Pops the specified length of data from the wrapped deserializer as `bytes::Bytes`.
+    #[cfg(feature = "bytes")]
+    fn pop_bytes(&mut self, len: usize) -> Result<bytes::Bytes> {
+        self.inner.pop_bytes(len)
+    }
+
+    /// Returns the number of bytes left in the wrapped deserializer.
+    fn remaining(&self) -> usize {
+        self.inner.remaining()
+    }
+
+    /// Checks the collection length with the wrapped deserializer.
+    fn check_collection_len(&mut self, len: usize, item_size: usize) -> Result<()> {
+        self.inner.check_collection_len(len, item_size)
+    }
+
+    /// Checks the byte length with the wrapped deserializer.
+    fn check_bytes_len(&mut self, len: usize) -> Result<()> {
+        self.inner.check_bytes_len(len)
+    }
+
+    /// Enters a nested value in the wrapped deserializer.
+    fn enter_nested(&mut self) -> Result<()> {
+        self.inner.enter_nested()
+    }
+
+    /// Leaves a nested value in the wrapped deserializer.
+    fn leave_nested(&mut self) {
+        self.inner.leave_nested();
+    }
+
+    /// Returns the decoded shared pointers.
+    fn shared_pointers(&self) -> Option<&SharedPointers> {
+        Some(&self.pointers)
+    }
+}
+
+/// Serializes a `Shared` pointer at the given address, writing its value with `f`.
+pub(crate) fn serialize_shared<S: Serializer>(
+    addr: usize,
+    serializer: &mut S,
+    f: impl FnOnce(&mut S) -> Result<()>,
+) -> Result<()> {
+    match serializer.visit_shared(addr) {
+        None => f(serializer),
+        Some(SharedVisit::Inline) => {
+            f(serializer)?;
+            VarInt64(0).serialize_to(serializer)
+        }
+        Some(SharedVisit::Full(id)) => {
+            f(serializer)?;
+            VarInt64(id + 1).serialize_to(serializer)
+        }
+        Some(SharedVisit::Ref(id)) => VarInt64(id + 1).serialize_to(serializer),
+    }
+}
+
+/// Deserializes a `Shared` pointer, reading its value with `f` unless it is a back-reference.
+pub(crate) fn deserialize_shared<'a, P: Clone + 'static, D: Deserializer<'a>>(
+    buf: &mut D,
+    f: impl FnOnce(&mut D) -> Result<P>,
+) -> Result<P> {
+    let Some(pointers) = buf.shared_pointers().cloned() else {
+        return f(buf);
+    };
+    let id = match VarInt64::deserialize_from(buf)?.0 {
+        0 => return f(buf),
+        id => id - 1,
+    };
+    match pointers.get(id) {
+        Some(pointer) => pointer,
+        None => {
+            let pointer = f(buf)?;
+            pointers.insert(id, pointer.clone());
+            Ok(pointer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Node {
+        name: String,
+        payload: Vec<u64>,
+        children: Vec<Shared<Arc<Node>>>,
+    }
+
+    fn leaf(name: &str) -> Shared<Arc<Node>> {
+        Shared(Arc::new(Node {
+            name: name.to_owned(),
+            payload: vec![7; 100],
+            children: vec![],
+        }))
+    }
+
+    fn serialize_shared<T: Serialize>(value: &T) -> DownwardBytes {
+        let mut ser = SharedSerializer::new(DownwardBytes::new());
+        ser.serialize_value(value).unwrap();
+        ser.into_inner()
+    }
+
+    #[test]
+    fn test_shared() {
+        let shared = leaf("shared");
+        let middle = Shared(Arc::new(Node {
+            name: "middle".to_owned(),
+            payload: vec![],
+            children: vec![shared.clone(), leaf("single"), shared.clone()],
+        }));
+        let root = Node {
+            name: "root".to_owned(),
+            payload: vec![],
+            children: vec![middle.clone(), shared.clone(), middle.clone()],
+        };
+
+        let plain = root.serialize::<DownwardBytes>().unwrap();
+        let bytes = serialize_shared(&root);
+        assert!(bytes.len() * 3 < plain.len());
+
+        let der = Node::deserialize(SharedDeserializer::new(&bytes[..])).unwrap();
+        assert_eq!(der, root);
+        assert_eq!(Node::deserialize(&plain[..]).unwrap(), root);
+        let middle = &der.children[0];
+        assert!(Arc::ptr_eq(middle, &der.children[2]));
+        assert!(Arc::ptr_eq(&middle.children[0], &der.children[1]));
+        assert!(Arc::ptr_eq(&middle.children[0], &middle.children[2]));
+        assert!(!Arc::ptr_eq(&middle.children[0], &middle.children[1]));
+        assert_eq!(Arc::strong_count(&der.children[1]), 3);
+
+        let der = Node::deserialize(LimitedDeserializer::new(
+            SharedDeserializer::new(&bytes[..]),
+            Limits::default(),
+        ))
+        .unwrap();
+        assert!(Arc::ptr_eq(&der.children[0], &der.children[2]));
+    }
+
+    #[test]
+    fn test_shared_types() {
+        let name: Shared<Rc<str>> = Shared(Rc::from("name"));
+        let items: Shared<Arc<[u32]>> = Shared(Arc::from(vec![1, 2, 3]));
+        let ser = (
+            name.clone(),
+            items.clone(),
+            name.clone(),
+            items.clone(),
+            Shared(Rc::new(1u8)),
+        );
+        let bytes = serialize_shared(&ser);
+
+        type Tuple = (
+            Shared<Rc<str>>,
+            Shared<Arc<[u32]>>,
+            Shared<Rc<str>>,
+            Shared<Arc<[u32]>>,
+            Shared<Rc<u8>>,
+        );
+        let der = Tuple::deserialize(SharedDeserializer::new(&bytes[..])).unwrap();
+        assert_eq!(der, ser);
+        assert!(Rc::ptr_eq(&der.0, &der.2));
+        assert!(Arc::ptr_eq(&der.1, &der.3));
+
+        let plain = ser.serialize::<DownwardBytes>().unwrap();
+        type PlainTuple = (Rc<str>, Arc<[u32]>, Rc<str>, Arc<[u32]>, Rc<u8>);
+        let der = PlainTuple::deserialize(&plain[..]).unwrap();
+        assert_eq!(&*der.0, "name");
+        assert!(!Rc::ptr_eq(&der.0, &der.2));
+
+        // Plain pointers are not tracked, even by a `SharedSerializer`.
+        let shared = Rc::new(1u8);
+        let bytes = serialize_shared(&(shared.clone(), shared.clone()));
+        assert_eq!(bytes, (1u8, 1u8).serialize::<DownwardBytes>().unwrap());
+
+        let mut ser = SharedSerializer::new(DownwardBytes::new());
+        ser.serialize_value(&(Shared(Rc::new(1u8)), 2u8)).unwrap();
+        ser.serialize_value(&(Shared(Rc::new(1u16)), 3u8)).unwrap();
+        let bytes = ser.into_inner();
+        let mut der = SharedDeserializer::new(&bytes[..]);
+        assert!(<(Shared<Rc<u16>>, u8)>::deserialize_from(&mut der).is_ok());
+        assert!(<(Shared<Rc<u8>>, u8)>::deserialize_from(&mut der).is_ok());
+
+        let shared = Shared(Rc::new(1u8));
+        let bytes = serialize_shared(&(shared.clone(), shared));
+        assert!(matches!(
+            <(Shared<Rc<u8>>, Shared<Rc<u16>>)>::deserialize(SharedDeserializer::new(&bytes[..])),
+            Err(Error::InvalidType(_))
+        ));
+    }
+}
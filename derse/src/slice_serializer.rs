@@ -0,0 +1,139 @@
+use super::{Error, Result, Serializer};
+
+/// A serializer writing backward into a caller-supplied byte slice.
+///
+/// The serialized data occupies the last `len()` bytes of the slice. Prepending more than
+/// the slice holds fails with `Error::BufferFull`. Counting the size with the `usize`
+/// serializer first allows sizing the slice exactly.
+pub struct SliceSerializer<'a> {
+    buf: &'a mut [u8],
+    offset: usize,
+}
+
+impl<'a> SliceSerializer<'a> {
+    /// Creates a new `SliceSerializer` writing into the given slice.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - The slice to write the data into, from its end.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        let offset = buf.len();
+        Self { buf, offset }
+    }
+
+    /// Returns the number of tail bytes used by the serialized data.
+    pub fn len(&self) -> usize {
+        self.buf.len() - self.offset
+    }
+
+    /// Checks if no data has been written.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the length of the underlying slice.
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Returns the serialized data.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[self.offset..]
+    }
+
+    /// Consumes the serializer, returning the serialized data as the tail of the slice.
+    pub fn into_slice(self) -> &'a mut [u8] {
+        &mut self.buf[self.offset..]
+    }
+
+    /// Prepends data to the slice.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data to prepend.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::BufferFull` if the data does not fit, leaving the slice unchanged.
+    pub fn prepend(&mut self, data: impl AsRef<[u8]>) -> Result<()> {
+        prepend_into(self.buf, &mut self.offset, data.as_ref())
+    }
+}
+
+/// Copies data in front of `buf[*offset..]` and moves `offset` to its start.
+///
+/// Returns `Error::BufferFull` if the data does not fit, leaving the buffer unchanged.
+pub(crate) fn prepend_into(buf: &mut [u8], offset: &mut usize, data: &[u8]) -> Result<()> {
+    if *offset < data.len() {
+        return Err(Error::BufferFull {
+            capacity: buf.len(),
+            required: (buf.len() - *offset).saturating_add(data.len()),
+        });
+    }
+    let new_offset = *offset - data.len();
+    buf[new_offset..*offset].copy_from_slice(data);
+    *offset = new_offset;
+    Ok(())
+}
+
+impl Serializer for SliceSerializer<'_> {
+    /// Prepends data to the slice, failing with `Error::BufferFull` if it does not fit.
+    fn prepend(&mut self, data: impl AsRef<[u8]>) -> Result<()> {
+        self.prepend(data)
+    }
+
+    /// Returns the number of tail bytes used by the serialized data.
+    fn len(&self) -> usize {
+        self.len()
+    }
+}
+
+impl std::fmt::Debug for SliceSerializer<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SliceSerializer({:?})", self.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_slice_serializer() {
+        let ser = (233u32, "slot".to_owned(), vec![1u64, 2, 3]);
+        let mut size = 0usize;
+        ser.serialize_to(&mut size).unwrap();
+
+        let mut slot = [0xffu8; 64];
+        let mut serializer = SliceSerializer::new(&mut slot[..size]);
+        ser.serialize_to(&mut serializer).unwrap();
+        assert_eq!(serializer.len(), size);
+        assert_eq!(serializer.capacity(), size);
+        assert_eq!(
+            serializer.as_slice(),
+            &ser.serialize::<DownwardBytes>().unwrap()[..]
+        );
+        assert_eq!(serializer.into_slice().len(), size);
+        assert_eq!(
+            <(u32, String, Vec<u64>)>::deserialize(&slot[..size]).unwrap(),
+            ser
+        );
+        assert_eq!(slot[size], 0xff);
+
+        let mut slot = [0u8; 64];
+        let mut serializer = SliceSerializer::new(&mut slot[..size - 1]);
+        assert_eq!(
+            ser.serialize_to(&mut serializer),
+            Err(Error::BufferFull {
+                capacity: size - 1,
+                required: size
+            })
+        );
+
+        let mut serializer = SliceSerializer::new(&mut slot);
+        assert!(serializer.is_empty());
+        serializer.prepend("tail").unwrap();
+        assert_eq!(serializer.len(), 4);
+        assert_eq!(&slot[60..], b"tail");
+    }
+}
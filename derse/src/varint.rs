@@ -0,0 +1,190 @@
+//! Variable-length integer encodings based on `VarInt64`.
+//!
+//! Unsigned integers are written as a `VarInt64` of their value, and signed integers are
+//! zig-zag encoded first, so that small magnitudes of either sign take few bytes. The
+//! `serialize_to` and `deserialize_from` functions let a field use this encoding with
+//! `#[derse(varint)]` or `#[derse(with = "derse::varint")]`, and the `Var*` wrapper types
+//! use it wherever a type is expected.
+
+use super::{Deserialize, Deserializer, Error, Result, Serialize, Serializer, VarInt64};
+
+/// An integer type that can be encoded as a `VarInt64`.
+pub trait VarIntValue: Copy {
+    /// Converts the integer into the value written as a `VarInt64`.
+    fn to_var_int(self) -> u64;
+
+    /// Converts a decoded `VarInt64` value back into the integer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::InvalidValue` if the value is out of range.
+    fn from_var_int(v: u64) -> Result<Self>;
+}
+
+macro_rules! unsigned_impl {
+    ($($t:ty),*) => {
+        $(impl VarIntValue for $t {
+            #[inline]
+            fn to_var_int(self) -> u64 {
+                self as u64
+            }
+
+            #[inline]
+            fn from_var_int(v: u64) -> Result<Self> {
+                <$t>::try_from(v).map_err(|_| out_of_range(v, stringify!($t)))
+            }
+        })*
+    };
+}
+
+unsigned_impl! {u8, u16, u32, u64, usize}
+
+macro_rules! signed_impl {
+    ($($t:ty),*) => {
+        $(impl VarIntValue for $t {
+            #[inline]
+            fn to_var_int(self) -> u64 {
+                let v = self as i64;
+                ((v << 1) ^ (v >> 63)) as u64
+            }
+
+            #[inline]
+            fn from_var_int(v: u64) -> Result<Self> {
+                let decoded = (v >> 1) as i64 ^ -((v & 1) as i64);
+                <$t>::try_from(decoded).map_err(|_| out_of_range(v, stringify!($t)))
+            }
+        })*
+    };
+}
+
+signed_impl! {i8, i16, i32, i64, isize}
+
+/// Returns the error for a decoded value that does not fit the integer type.
+fn out_of_range(v: u64, ty: &str) -> Error {
+    Error::InvalidValue(format!("varint {} is out of range for {}", v, ty))
+}
+
+/// Serializes the integer as a `VarInt64`.
+///
+/// # Arguments
+///
+/// * `value` - The integer to serialize.
+/// * `serializer` - The `Serializer` to serialize the data into.
+///
+/// # Returns
+///
+/// A `Result` indicating success or failure.
+pub fn serialize_to<T: VarIntValue, S: Serializer>(value: &T, serializer: &mut S) -> Result<()> {
+    VarInt64(value.to_var_int()).serialize_to(serializer)
+}
+
+/// Deserializes an integer written by `serialize_to`.
+///
+/// # Arguments
+///
+/// * `buf` - The `Deserializer` to deserialize the data from.
+///
+/// # Returns
+///
+/// A `Result` containing the integer or an error.
+pub fn deserialize_from<'a, T: VarIntValue, D: Deserializer<'a>>(buf: &mut D) -> Result<T> {
+    T::from_var_int(VarInt64::deserialize_from(buf)?.0)
+}
+
+macro_rules! wrapper_impl {
+    ($($name:ident($t:ty)),*) => {
+        $(#[doc = concat!("A `", stringify!($t), "` serialized as a variable-length integer.")]
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub $t);
+
+        impl From<$t> for $name {
+            fn from(v: $t) -> Self {
+                Self(v)
+            }
+        }
+
+        impl From<$name> for $t {
+            fn from(v: $name) -> Self {
+                v.0
+            }
+        }
+
+        impl Serialize for $name {
+            #[inline]
+            fn serialize_to<S: Serializer>(&self, serializer: &mut S) -> Result<()> {
+                serialize_to(&self.0, serializer)
+            }
+        }
+
+        impl<'a> Deserialize<'a> for $name {
+            #[inline]
+            fn deserialize_from<D: Deserializer<'a>>(buf: &mut D) -> Result<Self>
+            where
+                Self: Sized,
+            {
+                deserialize_from(buf).map(Self)
+            }
+        })*
+    };
+}
+
+wrapper_impl! {
+    VarU16(u16), VarU32(u32), VarU64(u64), VarUsize(usize),
+    VarI16(i16), VarI32(i32), VarI64(i64), VarIsize(isize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DownwardBytes;
+
+    #[test]
+    fn test_varint() {
+        for (v, len) in [
+            (0i64, 1),
+            (-1, 1),
+            (1, 1),
+            (-64, 1),
+            (64, 2),
+            (i64::MIN, 10),
+        ] {
+            let bytes = VarI64(v).serialize::<DownwardBytes>().unwrap();
+            assert_eq!(bytes.len(), len);
+            assert_eq!(VarI64::deserialize(&bytes[..]).unwrap(), VarI64(v));
+        }
+        assert_eq!(i64::MAX.to_var_int(), u64::MAX - 1);
+        assert_eq!(i64::MIN.to_var_int(), u64::MAX);
+        assert_eq!(i8::from_var_int(3).unwrap(), -2);
+
+        for v in [0u64, 127, 128, u64::MAX] {
+            let bytes = VarU64(v).serialize::<DownwardBytes>().unwrap();
+            assert_eq!(
+                bytes[..],
+                VarInt64(v).serialize::<DownwardBytes>().unwrap()[..]
+            );
+            assert_eq!(VarU64::deserialize(&bytes[..]).unwrap().0, v);
+        }
+
+        let bytes = VarU32(100).serialize::<DownwardBytes>().unwrap();
+        assert_eq!(bytes.len(), 1);
+        assert_eq!(VarU16::deserialize(&bytes[..]).unwrap(), VarU16(100));
+        assert_eq!(u8::from_var_int(100).unwrap(), 100);
+
+        let bytes = VarU64(1 << 40).serialize::<DownwardBytes>().unwrap();
+        assert_eq!(
+            VarU32::deserialize(&bytes[..]),
+            Err(Error::InvalidValue(format!(
+                "varint {} is out of range for u32",
+                1u64 << 40
+            )))
+        );
+        let bytes = VarI64(i64::from(i32::MIN) - 1)
+            .serialize::<DownwardBytes>()
+            .unwrap();
+        assert!(VarI32::deserialize(&bytes[..]).is_err());
+        let bytes = VarI64(i64::from(i32::MIN))
+            .serialize::<DownwardBytes>()
+            .unwrap();
+        assert_eq!(VarI32::deserialize(&bytes[..]).unwrap(), VarI32(i32::MIN));
+    }
+}
@@ -0,0 +1,257 @@
+use super::{BorrowingSerializer, DownwardBytes, Result, Serializer};
+use std::io::{IoSlice, Write};
+
+/// A serializer producing a chain of chunks for scatter-gather I/O.
+///
+/// Small data is packed into owned chunks, while byte sequences of at least
+/// `min_shared_len` bytes become chunks of their own, referencing the data instead of
+/// copying it. With `Serialize::serialize_borrowed_to`, these are the contents of
+/// `Vec<u8>`, `String`, slices and other byte sequences borrowed from the value for `'a`.
+/// With the `bytes` feature, `Bytes` buffers are referenced by any `Serialize` method.
+pub struct VectoredBytes<'a> {
+    head: DownwardBytes,
+    chunks: Vec<Chunk<'a>>,
+    len: usize,
+    min_shared_len: usize,
+}
+
+/// A chunk of a `VectoredBytes`.
+enum Chunk<'a> {
+    Borrowed(&'a [u8]),
+    Owned(Vec<u8>),
+    #[cfg(feature = "bytes")]
+    Shared(bytes::Bytes),
+}
+
+impl Chunk<'_> {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            Chunk::Borrowed(data) => data,
+            Chunk::Owned(data) => data,
+            #[cfg(feature = "bytes")]
+            Chunk::Shared(data) => data,
+        }
+    }
+}
+
+impl<'a> VectoredBytes<'a> {
+    /// The default minimum length of a byte sequence referenced as its own chunk.
+    pub const DEFAULT_MIN_SHARED_LEN: usize = 1024;
+
+    /// Creates a new empty `VectoredBytes` instance.
+    pub fn new() -> Self {
+        Self::with_min_shared_len(Self::DEFAULT_MIN_SHARED_LEN)
+    }
+
+    /// Creates a new empty `VectoredBytes` instance with the given threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_shared_len` - The minimum length of a byte sequence referenced as its own
+    ///   chunk. Shorter sequences are copied.
+    pub fn with_min_shared_len(min_shared_len: usize) -> Self {
+        Self {
+            head: DownwardBytes::new(),
+            chunks: Vec::new(),
+            len: 0,
+            min_shared_len,
+        }
+    }
+
+    /// Returns the total length of the serialized data.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over the chunks, in order.
+    pub fn chunks(&self) -> impl Iterator<Item = &[u8]> {
+        std::iter::once(self.head.as_slice())
+            .filter(|head| !head.is_empty())
+            .chain(self.chunks.iter().rev().map(Chunk::as_slice))
+    }
+
+    /// Returns the chunks as `IoSlice`s for `write_vectored`.
+    pub fn io_slices(&self) -> Vec<IoSlice<'_>> {
+        self.chunks().map(IoSlice::new).collect()
+    }
+
+    /// Copies the chunks into a contiguous `Vec<u8>`.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut vec = Vec::with_capacity(self.len);
+        self.chunks().for_each(|chunk| vec.extend_from_slice(chunk));
+        vec
+    }
+
+    /// Writes all chunks to the writer with vectored writes.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The writer to write the data to.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let mut slices = self.io_slices();
+        let mut slices = &mut slices[..];
+        while !slices.is_empty() {
+            match writer.write_vectored(slices) {
+                Ok(0) => return Err(std::io::Error::from(std::io::ErrorKind::WriteZero).into()),
+                Ok(n) => IoSlice::advance_slices(&mut slices, n),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the packed data in front of the chunks into a chunk of its own, and pushes
+    /// the given chunk.
+    fn push(&mut self, chunk: Chunk<'a>) {
+        if !self.head.is_empty() {
+            let head = std::mem::take(&mut self.head);
+            self.chunks.push(Chunk::Owned(head.into_vec()));
+        }
+        self.len += chunk.as_slice().len();
+        self.chunks.push(chunk);
+    }
+}
+
+impl Default for VectoredBytes<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer for VectoredBytes<'_> {
+    /// Prepends data to the buffer, copying it.
+    fn prepend(&mut self, data: impl AsRef<[u8]>) -> Result<()> {
+        let data = data.as_ref();
+        self.head.prepend(data);
+        self.len += data.len();
+        Ok(())
+    }
+
+    /// Prepends a `Bytes` buffer, referencing it as a chunk if it is long enough.
+    #[cfg(feature = "bytes")]
+    fn prepend_bytes(&mut self, data: &bytes::Bytes) -> Result<()> {
+        if data.len() < self.min_shared_len {
+            return self.prepend(data);
+        }
+        self.push(Chunk::Shared(data.clone()));
+        Ok(())
+    }
+
+    /// Returns the total length of the serialized data.
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a> BorrowingSerializer<'a> for VectoredBytes<'a> {
+    /// Prepends borrowed data, referencing it as a chunk if it is long enough.
+    fn prepend_borrowed(&mut self, data: &'a [u8]) -> Result<()> {
+        if data.len() < self.min_shared_len {
+            return self.prepend(data);
+        }
+        self.push(Chunk::Borrowed(data));
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for VectoredBytes<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.chunks()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Frame {
+        id: u32,
+        payload: Vec<u8>,
+        samples: Vec<u16>,
+        name: String,
+        small: Vec<u8>,
+    }
+
+    #[test]
+    fn test_vectored_bytes() {
+        let ser = Frame {
+            id: 233,
+            payload: vec![7u8; 4096],
+            samples: (0..1024).collect(),
+            name: "frame".to_owned(),
+            small: b"small".to_vec(),
+        };
+        let expected = ser.serialize::<DownwardBytes>().unwrap();
+
+        let mut bytes = VectoredBytes::new();
+        ser.serialize_borrowed_to(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), expected.len());
+        assert_eq!(bytes.to_vec(), &expected[..]);
+
+        let chunks: Vec<&[u8]> = bytes.chunks().collect();
+        assert_eq!(chunks.len(), 5);
+        assert_eq!(chunks[1].as_ptr(), ser.payload.as_ptr());
+        assert_eq!(chunks[3].as_ptr(), ser.samples.as_ptr() as *const u8);
+        assert_eq!(bytes.io_slices().len(), 5);
+        assert_eq!(Frame::deserialize(BytesArray::new(&chunks)).unwrap(), ser);
+
+        let mut written = Vec::new();
+        bytes.write_to(&mut written).unwrap();
+        assert_eq!(written, &expected[..]);
+        assert_eq!(Frame::deserialize(&written[..]).unwrap(), ser);
+
+        // `serialize_to` cannot borrow, so everything is copied.
+        let bytes = ser.serialize::<VectoredBytes>().unwrap();
+        assert_eq!(bytes.chunks().count(), 1);
+        assert_eq!(bytes.to_vec(), &expected[..]);
+
+        let mut bytes = VectoredBytes::with_min_shared_len(usize::MAX);
+        ser.serialize_borrowed_to(&mut bytes).unwrap();
+        assert_eq!(bytes.chunks().count(), 1);
+        assert_eq!(bytes.to_vec(), &expected[..]);
+
+        let bytes = VectoredBytes::new();
+        assert!(bytes.is_empty());
+        assert_eq!(bytes.chunks().count(), 0);
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_vectored_bytes_shared() {
+        use bytes::Bytes;
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Message {
+            id: u32,
+            payload: Bytes,
+            small: Bytes,
+            name: String,
+        }
+
+        let ser = Message {
+            id: 233,
+            payload: Bytes::from(vec![7u8; 4096]),
+            small: Bytes::from_static(b"small"),
+            name: "message".to_owned(),
+        };
+        let bytes = ser.serialize::<VectoredBytes>().unwrap();
+        let expected = ser.serialize::<DownwardBytes>().unwrap();
+        assert_eq!(bytes.to_vec(), &expected[..]);
+
+        let chunks: Vec<&[u8]> = bytes.chunks().collect();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[1].as_ptr(), ser.payload.as_ptr());
+        assert_eq!(Message::deserialize(BytesArray::new(&chunks)).unwrap(), ser);
+    }
+}
@@ -0,0 +1,43 @@
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(unknown)]
+struct T1 {
+    a: u32,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+struct T2 {
+    #[derse(unknown)]
+    a: u32,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+struct T3 {
+    #[derse(skip, skip)]
+    a: u32,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+struct T4 {
+    #[derse(skip, with = "module")]
+    a: u32,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+struct T5 {
+    #[derse(default)]
+    a: u32,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(from = "u32", try_from = "u32")]
+struct T6 {
+    a: u32,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+struct T7 {
+    #[derse(varint, with = "module")]
+    a: u32,
+}
+
+fn main() {}
@@ -0,0 +1,34 @@
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(tag = "number")]
+enum T1 {
+    A,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(tag = "index")]
+struct T2 {
+    a: u32,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(tagged)]
+enum T3 {
+    A,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(tag = "name")]
+enum T4 {
+    #[derse(id = 1)]
+    A,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+enum T5 {
+    #[derse(id = 1)]
+    A,
+    #[derse(rename = "b")]
+    B,
+}
+
+fn main() {}
@@ -0,0 +1,36 @@
+#[derive(derse::Serialize, derse::Deserialize)]
+enum T1 {
+    A,
+    #[derse(rename = "A")]
+    B,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+enum T2 {
+    #[derse(id = 1)]
+    A,
+    #[derse(id = 1)]
+    B,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+enum T3 {
+    #[derse(other)]
+    A,
+    #[derse(other)]
+    B,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+enum T4 {
+    #[derse(other)]
+    A(String, Vec<u8>, u32),
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+enum T5 {
+    #[derse(unknown)]
+    A,
+}
+
+fn main() {}
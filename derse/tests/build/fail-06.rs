@@ -0,0 +1,16 @@
+#[derive(derse::Serialize, derse::Deserialize)]
+struct T1 {
+    #[derse(id = 1)]
+    a: u32,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(tagged)]
+struct T2 {
+    #[derse(id = 1)]
+    a: u32,
+    #[derse(id = 1)]
+    b: u32,
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(transparent)]
+struct T1 {
+    a: u32,
+    b: u32,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(transparent)]
+struct T2;
+
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(transparent, tagged)]
+struct T3(u32);
+
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(transparent)]
+struct T4(#[derse(skip)] u32);
+
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(transparent)]
+enum T5 {
+    A(u32),
+}
+
+fn main() {}
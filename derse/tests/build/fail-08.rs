@@ -0,0 +1,17 @@
+#[derive(Clone, derse::Serialize, derse::Deserialize)]
+#[derse(transparent, into = "u32")]
+struct T1(u32);
+
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(from = "u32", tagged)]
+struct T2 {
+    a: u32,
+}
+
+#[derive(derse::Serialize, derse::Deserialize)]
+#[derse(tag = "index", try_from = "u32")]
+enum T3 {
+    A,
+}
+
+fn main() {}
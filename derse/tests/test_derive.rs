@@ -1,6 +1,9 @@
 use std::{borrow::Cow, marker::PhantomData};
 
-use derse::{BytesArray, Deserialize, Deserializer, DetailedDeserialize, DownwardBytes, Serialize};
+use derse::{
+    BytesArray, Deserialize, Deserializer, DetailedDeserialize, DownwardBytes, Serialize,
+    VectoredBytes,
+};
 
 #[test]
 fn test_named_struct() {
@@ -195,10 +198,936 @@ fn test_enum() {
     println!("{}", Demo::deserialize(&bytes[..]).unwrap_err());
 }
 
+#[test]
+fn test_enum_with_index_tag() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[derse(tag = "index")]
+    enum Demo {
+        A,
+        B(i32),
+        C { x: i32, y: String },
+    }
+
+    let ser = Demo::A;
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(bytes.len(), 1 + 1);
+    let der = Demo::deserialize(&bytes[..]).unwrap();
+    assert_eq!(ser, der);
+
+    let ser = Demo::B(233);
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(bytes.len(), 1 + 1 + 4);
+    assert_eq!(bytes[1], 1);
+    let der = Demo::deserialize(&bytes[..]).unwrap();
+    assert_eq!(ser, der);
+
+    let ser = Demo::C {
+        x: 233,
+        y: "hello".into(),
+    };
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(bytes[1], 2);
+    let der = Demo::deserialize(&bytes[..]).unwrap();
+    assert_eq!(ser, der);
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    enum WithId {
+        #[derse(id = 7)]
+        A,
+        B(i32),
+        #[derse(id = 1000)]
+        C,
+    }
+
+    for (ser, id) in [(WithId::A, 7u64), (WithId::B(1), 8), (WithId::C, 1000)] {
+        let bytes = ser.serialize::<DownwardBytes>().unwrap();
+        let tag = derse::VarInt64::deserialize(&bytes[1..]).unwrap();
+        assert_eq!(tag.0, id);
+        let der = WithId::deserialize(&bytes[..]).unwrap();
+        assert_eq!(ser, der);
+    }
+
+    let mut bytes = derse::VarInt64(3).serialize::<DownwardBytes>().unwrap();
+    1u8.serialize_to(&mut bytes).unwrap();
+    assert_eq!(
+        WithId::deserialize(&bytes[..])
+            .unwrap_err()
+            .root_cause()
+            .to_string(),
+        "invalid type: WithId::3"
+    );
+}
+
+#[test]
+fn test_enum_with_rename_and_alias() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    enum V1 {
+        Old,
+        Legacy(i32),
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    enum V2 {
+        #[derse(rename = "Old")]
+        New,
+        #[derse(rename = "Current", alias = "Legacy", alias = "Ancient")]
+        Current(i32),
+    }
+
+    let bytes = V1::Old.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(V2::deserialize(&bytes[..]).unwrap(), V2::New);
+    let bytes = V2::New.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(V1::deserialize(&bytes[..]).unwrap(), V1::Old);
+
+    let bytes = V1::Legacy(233).serialize::<DownwardBytes>().unwrap();
+    assert_eq!(V2::deserialize(&bytes[..]).unwrap(), V2::Current(233));
+
+    let ser = V2::Current(233);
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    let ty = <&str>::deserialize(&bytes[1..]).unwrap();
+    assert_eq!(ty, "Current");
+    assert_eq!(V2::deserialize(&bytes[..]).unwrap(), ser);
+    assert!(V1::deserialize(&bytes[..]).is_err());
+
+    let mut bytes = 7i32.serialize::<DownwardBytes>().unwrap();
+    "Ancient".serialize_to(&mut bytes).unwrap();
+    12u8.serialize_to(&mut bytes).unwrap();
+    assert_eq!(V2::deserialize(&bytes[..]).unwrap(), V2::Current(7));
+}
+
+#[test]
+fn test_enum_with_other() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    enum V2 {
+        A,
+        B(i32),
+        C { x: i32, y: String },
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    enum V1 {
+        A,
+        #[derse(other)]
+        Unknown,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    enum V1Raw {
+        A,
+        #[derse(other)]
+        Unknown(String, Vec<u8>),
+    }
+
+    let bytes = V2::A.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(V1::deserialize(&bytes[..]).unwrap(), V1::A);
+    assert_eq!(V1Raw::deserialize(&bytes[..]).unwrap(), V1Raw::A);
+
+    let ser = V2::C {
+        x: 233,
+        y: "hello".into(),
+    };
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(V1::deserialize(&bytes[..]).unwrap(), V1::Unknown);
+    let bytes = V1::Unknown.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(V1::deserialize(&bytes[..]).unwrap(), V1::Unknown);
+
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    let der = V1Raw::deserialize(&bytes[..]).unwrap();
+    let V1Raw::Unknown(tag, body) = &der else {
+        panic!("expect unknown variant");
+    };
+    assert_eq!(tag, "C");
+    assert_eq!(body.len(), 4 + 1 + 5);
+    let bytes2 = der.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(bytes, bytes2);
+    assert_eq!(V2::deserialize(&bytes2[..]).unwrap(), ser);
+
+    let mut vectored = VectoredBytes::with_min_shared_len(4);
+    der.serialize_borrowed_to(&mut vectored).unwrap();
+    assert_eq!(vectored.to_vec(), &bytes[..]);
+    assert!(vectored
+        .chunks()
+        .any(|chunk| chunk.as_ptr() == body.as_ptr()));
+
+    let (a, b) = bytes.split_at(6);
+    let der = V1Raw::deserialize(BytesArray::new(&[a, b])).unwrap();
+    assert_eq!(
+        der,
+        V1Raw::Unknown("C".to_owned(), bytes[bytes.len() - 10..].to_vec())
+    );
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[derse(tag = "index")]
+    enum V2Index {
+        A,
+        B(i32),
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    #[derse(tag = "index")]
+    enum V1Index {
+        A,
+        #[derse(other)]
+        Unknown {
+            id: u64,
+        },
+    }
+
+    let bytes = V2Index::B(233).serialize::<DownwardBytes>().unwrap();
+    let der = V1Index::deserialize(&bytes[..]).unwrap();
+    assert_eq!(der, V1Index::Unknown { id: 1 });
+    let bytes = der.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(bytes.len(), 1 + 1);
+    assert_eq!(V2Index::deserialize(&bytes[..]).unwrap(), V2Index::B(0));
+}
+
+#[test]
+fn test_field_attributes() {
+    struct NotSerializable;
+
+    mod as_string {
+        use derse::{Deserialize, Deserializer, Result, Serialize, Serializer};
+
+        pub fn serialize_to<S: Serializer>(value: &u32, serializer: &mut S) -> Result<()> {
+            value.to_string().serialize_to(serializer)
+        }
+
+        pub fn deserialize_from<'a, D: Deserializer<'a>>(buf: &mut D) -> Result<u32> {
+            let str = <&str>::deserialize_from(buf)?;
+            str.parse()
+                .map_err(|_| derse::Error::InvalidValue(str.to_owned()))
+        }
+    }
+
+    fn default_y() -> u64 {
+        233
+    }
+
+    #[derive(Deserialize, Serialize)]
+    struct A {
+        x: u64,
+        #[derse(skip)]
+        _z: Option<NotSerializable>,
+        #[derse(with = "as_string")]
+        w: u32,
+        #[derse(default = "default_y")]
+        y: u64,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct B {
+        x: u64,
+    }
+
+    let ser = A {
+        x: 1,
+        _z: Some(NotSerializable),
+        w: 12138,
+        y: 2,
+    };
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(bytes.len(), 1 + 8 + 1 + 5 + 8);
+    let der = A::deserialize(&bytes[..]).unwrap();
+    assert_eq!(der.x, 1);
+    assert!(der._z.is_none());
+    assert_eq!(der.w, 12138);
+    assert_eq!(der.y, 2);
+
+    let bytes = B { x: 1 }.serialize::<DownwardBytes>().unwrap();
+    let der = A::deserialize(&bytes[..]).unwrap();
+    assert_eq!(der.w, 0);
+    assert_eq!(der.y, 233);
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    enum E {
+        A(#[derse(skip)] u32, u64),
+        B {
+            #[derse(with = "as_string")]
+            x: u32,
+            #[derse(skip, default = "default_y")]
+            y: u64,
+        },
+    }
+
+    let ser = E::A(1, 2);
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(bytes.len(), 1 + 1 + 1 + 8);
+    let der = E::deserialize(&bytes[..]).unwrap();
+    assert_eq!(der, E::A(0, 2));
+
+    let ser = E::B { x: 7, y: 1 };
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(bytes.len(), 1 + 1 + 1 + 1 + 1);
+    let der = E::deserialize(&bytes[..]).unwrap();
+    assert_eq!(der, E::B { x: 7, y: 233 });
+}
+
+#[test]
+fn test_tagged_struct() {
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    #[derse(tagged)]
+    struct V1 {
+        #[derse(id = 1)]
+        a: u64,
+        b: String,
+        #[derse(skip)]
+        c: u32,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    #[derse(tagged)]
+    struct V2 {
+        #[derse(id = 5)]
+        d: Vec<u8>,
+        #[derse(id = 2)]
+        b: String,
+    }
+
+    let ser = V1 {
+        a: 233,
+        b: "hello".into(),
+        c: 1,
+    };
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(bytes.len(), 1 + (1 + 1 + 8) + (1 + 1 + 1 + 5));
+    let der = V1::deserialize(&bytes[..]).unwrap();
+    assert_eq!(der.a, 233);
+    assert_eq!(der.b, "hello");
+    assert_eq!(der.c, 0);
+
+    let der = V2::deserialize(&bytes[..]).unwrap();
+    assert!(der.d.is_empty());
+    assert_eq!(der.b, "hello");
+
+    let ser = V2 {
+        d: vec![1, 2, 3],
+        b: "world".into(),
+    };
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    let der = V2::deserialize(&bytes[..]).unwrap();
+    assert_eq!(ser, der);
+    let mut vectored = VectoredBytes::with_min_shared_len(3);
+    ser.serialize_borrowed_to(&mut vectored).unwrap();
+    assert_eq!(vectored.to_vec(), &bytes[..]);
+    assert!(vectored
+        .chunks()
+        .any(|chunk| chunk.as_ptr() == ser.d.as_ptr()));
+    let der = V1::deserialize(&bytes[..]).unwrap();
+    assert_eq!(der.a, 0);
+    assert_eq!(der.b, "world");
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    #[derse(tagged)]
+    struct Empty;
+
+    let der = Empty::deserialize(&bytes[..]).unwrap();
+    assert_eq!(der, Empty);
+    let bytes = Empty.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(bytes.len(), 1);
+    assert_eq!(V2::deserialize(&bytes[..]).unwrap().b, "");
+
+    let mut bytes = DownwardBytes::new();
+    2u8.serialize_to(&mut bytes).unwrap();
+    2u8.serialize_to(&mut bytes).unwrap();
+    assert!(V2::deserialize(&bytes[..]).is_err());
+}
+
+#[test]
+fn test_transparent_struct() {
+    #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+    #[derse(transparent)]
+    struct UserId(u64);
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    #[derse(transparent)]
+    struct Name<'a> {
+        name: Cow<'a, str>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Old {
+        id: u64,
+        names: Vec<String>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct New<'a> {
+        id: UserId,
+        names: Vec<Name<'a>>,
+    }
+
+    let id = UserId(u64::MAX);
+    let bytes = id.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(
+        &bytes[..],
+        &u64::MAX.serialize::<DownwardBytes>().unwrap()[..]
+    );
+    assert_eq!(id.serialize::<usize>().unwrap(), 8);
+    assert_eq!(UserId::deserialize(&bytes[..]).unwrap(), id);
+
+    let old = Old {
+        id: 42,
+        names: vec!["alice".to_owned(), "bob".to_owned()],
+    };
+    let bytes = old.serialize::<DownwardBytes>().unwrap();
+    let new = New::deserialize(&bytes[..]).unwrap();
+    assert_eq!(new.id, UserId(42));
+    assert_eq!(new.names[1].name, "bob");
+    assert!(matches!(new.names[0].name, Cow::Borrowed(_)));
+    assert_eq!(&new.serialize::<DownwardBytes>().unwrap()[..], &bytes[..]);
+}
+
+#[test]
+fn test_conversion_attributes() {
+    #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+    #[derse(into = "u8", try_from = "u8")]
+    struct Percent(u8);
+
+    impl From<Percent> for u8 {
+        fn from(value: Percent) -> Self {
+            value.0
+        }
+    }
+
+    impl TryFrom<u8> for Percent {
+        type Error = String;
+
+        fn try_from(value: u8) -> Result<Self, Self::Error> {
+            if value <= 100 {
+                Ok(Self(value))
+            } else {
+                Err(format!("{value} is greater than 100"))
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+    #[derse(into = "String", from = "String")]
+    enum Host {
+        Local,
+        Remote(String),
+    }
+
+    impl From<Host> for String {
+        fn from(value: Host) -> Self {
+            match value {
+                Host::Local => "localhost".to_owned(),
+                Host::Remote(name) => name,
+            }
+        }
+    }
+
+    impl From<String> for Host {
+        fn from(value: String) -> Self {
+            match value.as_str() {
+                "localhost" => Host::Local,
+                _ => Host::Remote(value),
+            }
+        }
+    }
+
+    let ser = Percent(42);
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(&bytes[..], &[42]);
+    assert_eq!(Percent::deserialize(&bytes[..]).unwrap(), ser);
+    assert_eq!(
+        Percent::deserialize(&[101][..]),
+        Err(derse::Error::InvalidValue(
+            "101 is greater than 100".to_owned()
+        ))
+    );
+
+    for ser in [Host::Local, Host::Remote("example.com".to_owned())] {
+        let bytes = ser.serialize::<DownwardBytes>().unwrap();
+        let name: String = ser.clone().into();
+        assert_eq!(&bytes[..], &name.serialize::<DownwardBytes>().unwrap()[..]);
+        assert_eq!(Host::deserialize(&bytes[..]).unwrap(), ser);
+    }
+}
+
+#[test]
+fn test_validate_attribute() {
+    #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+    #[derse(validate = "Range::check")]
+    struct Range {
+        start: u32,
+        end: u32,
+    }
+
+    impl Range {
+        fn check(&self) -> Result<(), String> {
+            if self.start <= self.end {
+                Ok(())
+            } else {
+                Err(format!("invalid range {}..{}", self.start, self.end))
+            }
+        }
+    }
+
+    fn non_empty(name: &Name) -> Result<(), String> {
+        if name.0.is_empty() {
+            Err("name is empty".to_owned())
+        } else {
+            Ok(())
+        }
+    }
+
+    #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+    #[derse(transparent, validate = "non_empty")]
+    struct Name(String);
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Item {
+        name: Name,
+        range: Range,
+    }
+
+    let ser = Item {
+        name: Name("item".to_owned()),
+        range: Range { start: 1, end: 2 },
+    };
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(Item::deserialize(&bytes[..]).unwrap(), ser);
+
+    let ser = Item {
+        name: Name("item".to_owned()),
+        range: Range { start: 2, end: 1 },
+    };
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(
+        Item::deserialize(&bytes[..]).unwrap_err().root_cause(),
+        &derse::Error::InvalidValue("invalid range 2..1".to_owned())
+    );
+
+    let ser = Item {
+        name: Name(String::new()),
+        range: Range::default(),
+    };
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(
+        Item::deserialize(&bytes[..]).unwrap_err().root_cause(),
+        &derse::Error::InvalidValue("name is empty".to_owned())
+    );
+}
+
+#[test]
+fn test_error_context() {
+    #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+    struct Item {
+        id: u32,
+        name: String,
+    }
+
+    #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+    struct Outer {
+        version: u8,
+        items: Vec<Item>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    enum Message {
+        Empty,
+        Data(Outer),
+    }
+
+    let ser = Outer {
+        version: 1,
+        items: (0..5)
+            .map(|id| Item {
+                id,
+                name: format!("item{id}"),
+            })
+            .collect(),
+    };
+    let mut bytes = ser.serialize::<DownwardBytes>().unwrap().to_vec();
+    // Corrupts the first byte of `items[3].name`.
+    let offset = bytes.windows(5).position(|w| w == b"item3").unwrap();
+    bytes[offset] = 0xff;
+
+    let err = Outer::deserialize(&bytes[..]).unwrap_err();
+    assert!(matches!(err.root_cause(), derse::Error::InvalidString(_)));
+    match &err {
+        derse::Error::Context(context) => {
+            assert_eq!(context.ty, "Outer");
+            assert_eq!(context.path, ".items[3].name");
+            assert_eq!(context.offset, offset + 5);
+        }
+        _ => panic!("unexpected error {err}"),
+    }
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Outer.items[3].name at offset {}: invalid string: {:?}",
+            offset + 5,
+            &bytes[offset..offset + 5]
+        )
+    );
+
+    let ser = Message::Data(ser);
+    let mut message = ser.serialize::<DownwardBytes>().unwrap().to_vec();
+    let offset = message.windows(5).position(|w| w == b"item4").unwrap();
+    message[offset] = 0xff;
+    let err = Message::deserialize(&message[..]).unwrap_err();
+    assert!(err.to_string().starts_with(&format!(
+        "Message.Data.0.items[4].name at offset {}",
+        offset + 5
+    )));
+
+    let err = Vec::<Outer>::deserialize(&[1u8, 2, 0][..]).unwrap_err();
+    let derse::Error::Context(context) = &err else {
+        panic!("unexpected error {err}");
+    };
+    assert_eq!(context.ty, "");
+    assert_eq!(context.path, "[0]");
+    assert_eq!(context.offset, 2);
+    assert_eq!(
+        context.error,
+        derse::Error::DataIsShort {
+            expect: 2,
+            actual: 1
+        }
+    );
+    assert!(matches!(err.root_cause(), derse::Error::DataIsShort { .. }));
+
+    let mut der = derse::ReaderDeserializer::new(&bytes[..]);
+    let err = Outer::deserialize_from(&mut der).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        Outer::deserialize(&bytes[..]).unwrap_err().to_string()
+    );
+}
+
+#[test]
+fn test_smart_pointers() {
+    #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+    enum Expr {
+        #[default]
+        Zero,
+        Num(i64),
+        Neg(Box<Expr>),
+        Add(Box<Expr>, Box<Expr>),
+        Sum(Box<[Expr]>),
+    }
+
+    impl Expr {
+        fn eval(&self) -> i64 {
+            match self {
+                Expr::Zero => 0,
+                Expr::Num(n) => *n,
+                Expr::Neg(e) => -e.eval(),
+                Expr::Add(a, b) => a.eval() + b.eval(),
+                Expr::Sum(items) => items.iter().map(Expr::eval).sum(),
+            }
+        }
+    }
+
+    let ser = Expr::Add(
+        Box::new(Expr::Num(1)),
+        Box::new(Expr::Neg(Box::new(Expr::Sum(
+            vec![Expr::Num(2), Expr::Num(3)].into(),
+        )))),
+    );
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    let der = Expr::deserialize(&bytes[..]).unwrap();
+    assert_eq!(der, ser);
+    assert_eq!(der.eval(), -4);
+
+    #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+    struct Shared {
+        name: std::sync::Arc<str>,
+        tags: std::rc::Rc<[String]>,
+        value: std::sync::Arc<u64>,
+    }
+
+    #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+    struct Plain {
+        name: String,
+        tags: Vec<String>,
+        value: u64,
+    }
+
+    let ser = Shared {
+        name: "shared".into(),
+        tags: vec!["a".to_owned(), "b".to_owned()].into(),
+        value: 7.into(),
+    };
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(Shared::deserialize(&bytes[..]).unwrap(), ser);
+    let plain = Plain::deserialize(&bytes[..]).unwrap();
+    assert_eq!(plain.name, "shared");
+    assert_eq!(plain.tags, ["a", "b"]);
+    assert_eq!(plain.value, 7);
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Borrowing<'a> {
+        name: std::rc::Rc<Cow<'a, str>>,
+        names: std::sync::Arc<[&'a str]>,
+    }
+
+    let ser = Borrowing {
+        name: std::rc::Rc::new(Cow::Borrowed("borrowed")),
+        names: vec!["a", "b"].into(),
+    };
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    let der = Borrowing::deserialize(&bytes[..]).unwrap();
+    assert_eq!(der, ser);
+    assert!(matches!(*der.name, Cow::Borrowed(_)));
+}
+
+#[test]
+fn test_varint_attribute() {
+    #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+    struct Fixed {
+        count: u64,
+        delta: i32,
+        id: u16,
+    }
+
+    #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+    struct Compact {
+        #[derse(varint)]
+        count: u64,
+        #[derse(varint)]
+        delta: i32,
+        #[derse(with = "derse::varint")]
+        id: u16,
+    }
+
+    #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+    struct Wrapped(derse::VarU64, derse::VarI32, derse::VarU16);
+
+    let fixed = Fixed {
+        count: 42,
+        delta: -3,
+        id: 7,
+    };
+    let compact = Compact {
+        count: 42,
+        delta: -3,
+        id: 7,
+    };
+    let fixed = fixed.serialize::<DownwardBytes>().unwrap();
+    let bytes = compact.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(fixed.len(), 1 + 8 + 4 + 2);
+    assert_eq!(bytes.len(), 1 + 3);
+    assert_eq!(Compact::deserialize(&bytes[..]).unwrap(), compact);
+
+    let wrapped = Wrapped::deserialize(&bytes[..]).unwrap();
+    assert_eq!(
+        wrapped,
+        Wrapped(derse::VarU64(42), derse::VarI32(-3), derse::VarU16(7))
+    );
+    assert_eq!(wrapped.serialize::<DownwardBytes>().unwrap()[..], bytes[..]);
+
+    #[derive(Serialize)]
+    struct Wide(
+        #[derse(varint)] u64,
+        #[derse(varint)] i64,
+        #[derse(varint)] u64,
+    );
+
+    let bytes = Wide(42, -3, 1 << 20).serialize::<DownwardBytes>().unwrap();
+    assert_eq!(
+        Compact::deserialize(&bytes[..]).unwrap_err().to_string(),
+        format!(
+            "Compact.id at offset {}: invalid value: varint 1048576 is out of range for u16",
+            bytes.len()
+        )
+    );
+}
+
+#[test]
+fn test_reader_deserializer() {
+    #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+    struct A {
+        x: u64,
+        y: Vec<String>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    enum B {
+        X(A),
+        Y { a: A, b: Option<A> },
+    }
+
+    let items = [
+        B::X(A {
+            x: 1,
+            y: vec!["hello".to_owned()],
+        }),
+        B::Y {
+            a: A::default(),
+            b: Some(A {
+                x: 2,
+                y: vec!["derse".to_owned(); 3],
+            }),
+        },
+    ];
+    let mut data = Vec::new();
+    for item in &items {
+        data.extend_from_slice(&item.serialize::<DownwardBytes>().unwrap());
+    }
+
+    let mut der = derse::ReaderDeserializer::new(std::io::BufReader::new(&data[..]));
+    for item in &items {
+        assert_eq!(&B::deserialize_from(&mut der).unwrap(), item);
+    }
+    assert!(B::deserialize_from(&mut der).is_err());
+}
+
+#[test]
+fn test_recursion_depth_limit() {
+    #[derive(Debug, Default, Deserialize, Serialize, PartialEq)]
+    struct Node {
+        value: u32,
+        children: Vec<Node>,
+    }
+
+    let ser = Node {
+        value: 1,
+        children: vec![
+            Node::default(),
+            Node {
+                value: 2,
+                children: vec![Node::default()],
+            },
+        ],
+    };
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    let limits = derse::Limits {
+        max_depth: 6,
+        ..Default::default()
+    };
+    let der = Node::deserialize(derse::LimitedDeserializer::new(&bytes[..], limits)).unwrap();
+    assert_eq!(der, ser);
+
+    let limits = derse::Limits {
+        max_depth: 5,
+        ..Default::default()
+    };
+    assert!(matches!(
+        Node::deserialize(derse::LimitedDeserializer::new(&bytes[..], limits))
+            .unwrap_err()
+            .root_cause(),
+        derse::Error::LimitExceeded(_)
+    ));
+
+    // Builds a chain of nodes too deep to deserialize recursively on the stack.
+    let mut bytes = DownwardBytes::new();
+    for _ in 0..1_000_000 {
+        derse::VarInt64(1).serialize_to(&mut bytes).unwrap();
+        0u32.serialize_to(&mut bytes).unwrap();
+        derse::VarInt64(bytes.len() as u64)
+            .serialize_to(&mut bytes)
+            .unwrap();
+    }
+    let limits = derse::Limits {
+        max_depth: 128,
+        ..Default::default()
+    };
+    assert!(matches!(
+        Node::deserialize(derse::LimitedDeserializer::new(&bytes[..], limits))
+            .unwrap_err()
+            .root_cause(),
+        derse::Error::LimitExceeded(_)
+    ));
+
+    // Transparent and converted types have no length prefix, but still count as a level.
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    #[derse(transparent)]
+    struct Chain(Option<Box<Chain>>);
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[derse(from = "Chain")]
+    struct Converted(Chain);
+
+    impl From<Chain> for Converted {
+        fn from(chain: Chain) -> Self {
+            Self(chain)
+        }
+    }
+
+    let ser = Chain(Some(Box::new(Chain(None))));
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    let limits = derse::Limits {
+        max_depth: 2,
+        ..Default::default()
+    };
+    let der = Chain::deserialize(derse::LimitedDeserializer::new(&bytes[..], limits)).unwrap();
+    assert_eq!(der, ser);
+    let limits = derse::Limits {
+        max_depth: 1,
+        ..Default::default()
+    };
+    assert!(Chain::deserialize(derse::LimitedDeserializer::new(&bytes[..], limits)).is_err());
+    assert!(Converted::deserialize(derse::LimitedDeserializer::new(&bytes[..], limits)).is_err());
+
+    let bytes = vec![1u8; 1_000_000];
+    let limits = derse::Limits {
+        max_depth: 16,
+        ..Default::default()
+    };
+    assert!(matches!(
+        Chain::deserialize(derse::LimitedDeserializer::new(&bytes[..], limits))
+            .unwrap_err()
+            .root_cause(),
+        derse::Error::LimitExceeded(_)
+    ));
+    assert!(matches!(
+        Converted::deserialize(derse::LimitedDeserializer::new(&bytes[..], limits))
+            .unwrap_err()
+            .root_cause(),
+        derse::Error::LimitExceeded(_)
+    ));
+}
+
+mod facade {
+    pub use derse as inner;
+}
+
+mod custom_crate {
+    use crate::facade::inner;
+
+    #[derive(Debug, Default, PartialEq, inner::Serialize, inner::Deserialize)]
+    #[derse(crate = "crate::facade::inner")]
+    pub struct A {
+        pub x: u32,
+        pub y: String,
+    }
+
+    #[derive(Debug, PartialEq, inner::Serialize, inner::Deserialize)]
+    #[derse(crate = "crate::facade::inner")]
+    pub enum B {
+        X(A),
+        Y,
+    }
+}
+
+#[test]
+fn test_custom_crate_path() {
+    use custom_crate::{A, B};
+
+    let ser = B::X(A {
+        x: 1,
+        y: "hello".to_owned(),
+    });
+    let bytes = ser.serialize::<DownwardBytes>().unwrap();
+    let der = B::deserialize(&bytes[..]).unwrap();
+    assert_eq!(ser, der);
+
+    let bytes = B::Y.serialize::<DownwardBytes>().unwrap();
+    assert_eq!(B::deserialize(&bytes[..]).unwrap(), B::Y);
+    assert!(matches!(
+        B::deserialize(&bytes[..bytes.len() - 1])
+            .unwrap_err()
+            .root_cause(),
+        derse::Error::DataIsShort { .. }
+    ));
+}
+
 #[test]
 fn test_build() {
     let t = trybuild::TestCases::new();
     t.pass("tests/build/succ.rs");
-    t.compile_fail("tests/build/fail-01.rs");
-    t.compile_fail("tests/build/fail-02.rs");
+    t.compile_fail("tests/build/fail-*.rs");
 }